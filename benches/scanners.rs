@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+//! Simple benchmarks comparing alternative scanning approaches. Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use chscn::{CharSet, OperatorSet, Text};
+
+fn bench<F: FnMut() -> usize>(name: &str, iterations: u32, mut f: F) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    println!("{:<32} {:>12.2?} per iteration", name, start.elapsed() / iterations);
+}
+
+const OPERATORS: &[&str] = &[
+    "+", "+=", "-", "-=", "->", "*", "*=", "/", "/=", "%", "%=", "^", "^=", "!", "!=", "=", "==",
+    "=>", "<", "<=", "<<", "<<=", ">", ">=", ">>", ">>=", "&", "&&", "&=", "|", "||", "|=", ".",
+    "..", "..=", "::", ":", ";", ",",
+];
+
+fn operators() {
+    let source = "a <<= b << c < d >>= e >> f > g == h != i <= j && k || l += m .. n ..= o :: p; "
+        .repeat(1000);
+    let set = OperatorSet::with_operators(OPERATORS);
+
+    bench("scan_operator (linear)", 20, || {
+        let mut text = Text::with_str(&source);
+        let mut count = 0;
+        while text.peek_next().is_some() {
+            match text.scan_operator(OPERATORS) {
+                Some(_) => count += 1,
+                None => { let _ = text.next(); },
+            }
+        }
+        count
+    });
+    bench("scan_operator_set (trie)", 20, || {
+        let mut text = Text::with_str(&source);
+        let mut count = 0;
+        while text.peek_next().is_some() {
+            match text.scan_operator_set(&set) {
+                Some(_) => count += 1,
+                None => { let _ = text.next(); },
+            }
+        }
+        count
+    });
+}
+
+fn charset() {
+    let source = "foo_bar1 baz_2 qux_quux_3 corge ".repeat(2000);
+    let set = CharSet::from_ranges(&[('a', 'z'), ('0', '9'), ('_', '_')]);
+
+    bench("consume_while_on_line (closure)", 20, || {
+        let mut text = Text::with_str(&source);
+        let mut count = 0;
+        while text.peek_next().is_some() {
+            count += text.consume_while_on_line(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_').len();
+            let _ = text.next();
+        }
+        count
+    });
+    bench("scan_charset_run", 20, || {
+        let mut text = Text::with_str(&source);
+        let mut count = 0;
+        while text.peek_next().is_some() {
+            count += text.scan_charset_run(&set).len();
+            let _ = text.next();
+        }
+        count
+    });
+}
+
+fn until_byte() {
+    let source = "key = \"wert für ärger\"\n  next line: ö ∑ ok ".repeat(2000) + ";";
+
+    bench("consume until ';' (chars)", 20, || {
+        let mut text = Text::with_str(&source);
+        let mut count = 0;
+        while text.peek_next().is_some_and(|ch| ch != ';') {
+            let _ = text.next();
+            count += 1;
+        }
+        count
+    });
+    bench("scan_until_byte", 20, || {
+        let mut text = Text::with_str(&source);
+        text.scan_until_byte(b';').len()
+    });
+}
+
+fn main() {
+    operators();
+    charset();
+    until_byte();
+}
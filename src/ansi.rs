@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Spanned, Text};
+
+/// Returns the length in bytes of the ANSI control sequence (`ESC [` parameters intermediates
+/// final) at the start of `s` or `None` if `s` does not start with a complete one.
+fn csi_len(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    if !b.starts_with(b"\x1b[") {
+        return None;
+    }
+    let mut len = 2;
+    len += b[len..].iter().take_while(|b| (0x30..=0x3f).contains(*b)).count();
+    len += b[len..].iter().take_while(|b| (0x20..=0x2f).contains(*b)).count();
+    match b.get(len) {
+        Some(0x40..=0x7e) => Some(len + 1),
+        _ => None,
+    }
+}
+
+impl<'a> Text<'a> {
+    /// Scans an ANSI control sequence (CSI) like `\x1b[1;31m`, i.e. `ESC [` followed by parameter
+    /// and intermediate bytes and a final byte in the range `@` to `~`.
+    /// Returns `None` without consuming anything if no complete sequence starts at the reading
+    /// position.
+    pub fn scan_ansi_escape(&mut self) -> Option<Spanned<&'a str>> {
+        let len = csi_len(self.remaining())?;
+        let start = self.position.clone();
+        let sequence = self.consume_bytes(len);
+        Some(self.spanned(start, sequence))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_ansi_escape() {
+        let mut text = Text::with_str("\x1b[1;31merror\x1b[0m");
+
+        let sequence = text.scan_ansi_escape().unwrap();
+        assert_eq!(sequence.value, "\x1b[1;31m");
+        assert_eq!(sequence.span, Span::with(Position::with(1, 1), Position::with(1, 8)));
+        assert_eq!(text.scan_ansi_escape(), None);
+        assert_eq!(text.next(), Some('e'));
+    }
+
+    #[test]
+    fn scan_ansi_escape_incomplete() {
+        let mut text = Text::with_str("\x1b[1;3");
+
+        assert_eq!(text.scan_ansi_escape(), None);
+        assert_eq!(text.position(), &Position::with(1, 1));
+    }
+}
@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ident::ident_len;
+use crate::{Position, ScanError, Spanned, Text};
+
+impl<'a> Text<'a> {
+    /// Consumes the rest of a quoted string whose opening `quote` at `start` has already been
+    /// consumed. `escape` escapes the following char.
+    fn skip_quoted(&mut self, quote: char, escape: char, start: Position) -> Result<(), ScanError> {
+        loop {
+            match self.next() {
+                Some(ch) if ch == quote => return Ok(()),
+                Some(ch) if ch == escape => { let _ = self.next(); },
+                Some(_) => {},
+                None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+            }
+        }
+    }
+
+    /// Scans a region enclosed by `open` and `close` that may contain nested pairs of `open` and
+    /// `close`. String literals delimited by one of `quotes` (where `escape` escapes the following
+    /// char) are skipped, so brackets within them are ignored.
+    /// Returns the content between the outermost brackets; the span covers the brackets.
+    pub fn scan_balanced_respecting_strings(&mut self, open: char, close: char, quotes: &[char], escape: char) -> Result<Spanned<&'a str>, ScanError> {
+        let start = self.position.clone();
+        self.expect_char(open)?;
+        let inner_start = self.offset();
+        let mut depth = 0usize;
+        loop {
+            let at = self.position.clone();
+            let inner_end = self.offset();
+            match self.next() {
+                Some(ch) if ch == close && depth == 0 =>
+                    return Ok(self.spanned(start, &self.source[inner_start..inner_end])),
+                Some(ch) if ch == close => depth -= 1,
+                Some(ch) if ch == open => depth += 1,
+                Some(ch) if quotes.contains(&ch) => self.skip_quoted(ch, escape, at)?,
+                Some(_) => {},
+                None => return Err(ScanError::Unterminated{ start, at }),
+            }
+        }
+    }
+
+    /// Scans a region enclosed by `open` and `close` that may contain nested pairs of `open` and
+    /// `close` and returns the content between the outermost brackets; the span covers the
+    /// brackets. Returns `ScanError::ExpectedClose` without an opener position if the text starts
+    /// with a stray `close`, and with the position of the innermost unmatched `open` at EOF.
+    pub fn scan_balanced_diagnosed(&mut self, open: char, close: char) -> Result<Spanned<&'a str>, ScanError> {
+        let start = self.position.clone();
+        if self.peek_next() == Some(close) {
+            return Err(ScanError::ExpectedClose{ opener_at: None, found: Some(close), at: start });
+        }
+        self.expect_char(open)?;
+        let inner_start = self.offset();
+        let mut openers = vec![start.clone()];
+        loop {
+            let at = self.position.clone();
+            let inner_end = self.offset();
+            match self.next() {
+                Some(ch) if ch == close => {
+                    openers.pop();
+                    if openers.is_empty() {
+                        return Ok(self.spanned(start, &self.source[inner_start..inner_end]));
+                    }
+                },
+                Some(ch) if ch == open => openers.push(at),
+                Some(_) => {},
+                None => return Err(ScanError::ExpectedClose{ opener_at: openers.pop(), found: None, at }),
+            }
+        }
+    }
+
+    /// Scans generic type arguments like `<HashMap<K, V>>` enclosed by `<` and `>`, which must be
+    /// next. Each `>` closes one level, so `>>` closes two; the `>` of an arrow `->` is ignored.
+    /// Returns the content between the outermost brackets; the span covers the brackets.
+    /// Returns `ScanError::ExpectedClose` with the position of the innermost unmatched `<` at EOF.
+    pub fn scan_generic_args(&mut self) -> Result<Spanned<&'a str>, ScanError> {
+        let start = self.position.clone();
+        self.expect_char('<')?;
+        let inner_start = self.offset();
+        let mut openers = vec![start.clone()];
+        loop {
+            let at = self.position.clone();
+            let inner_end = self.offset();
+            match self.next() {
+                Some('>') => {
+                    openers.pop();
+                    if openers.is_empty() {
+                        return Ok(self.spanned(start, &self.source[inner_start..inner_end]));
+                    }
+                },
+                Some('<') => openers.push(at),
+                Some('-') if self.peek_next() == Some('>') => { let _ = self.next(); },
+                Some(_) => {},
+                None => return Err(ScanError::ExpectedClose{ opener_at: openers.pop(), found: None, at }),
+            }
+        }
+    }
+
+    /// Scans a block enclosed by the keywords `begin` and `end` (like `begin ... end` in Pascal)
+    /// that may contain nested blocks. The keywords are matched as whole identifiers, so `ending`
+    /// does not end a block. Returns the content between the outermost keywords; the span covers
+    /// the keywords. Returns `ScanError::Unexpected` if `begin` is not next.
+    pub fn scan_keyword_block(&mut self, begin: &str, end: &str) -> Result<Spanned<&'a str>, ScanError> {
+        let start = self.position.clone();
+        let word = |text: &Text<'a>| {
+            let rest = text.remaining();
+            &rest[..ident_len(rest, &[], &[])]
+        };
+        if word(self) != begin {
+            return Err(ScanError::Unexpected{ expected: "block", found: self.peek_next(), at: start });
+        }
+        self.consume_bytes(begin.len());
+        let inner_start = self.offset();
+        let mut depth = 0usize;
+        loop {
+            let inner_end = self.offset();
+            let word = word(self);
+            if word.is_empty() {
+                match self.next() {
+                    Some(ch) if ch.is_alphanumeric() => {
+                        while self.peek_next().is_some_and(|ch| ch.is_alphanumeric() || ch == '_') {
+                            let _ = self.next();
+                        }
+                    },
+                    Some(_) => {},
+                    None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+                }
+                continue;
+            }
+            self.consume_bytes(word.len());
+            if word == end {
+                if depth == 0 {
+                    return Ok(self.spanned(start, &self.source[inner_start..inner_end]));
+                }
+                depth -= 1;
+            }
+            else if word == begin {
+                depth += 1;
+            }
+        }
+    }
+
+    /// Consumes text until one of the `stop` chars appears outside of any pair of `open` and
+    /// `close` (e.g. up to a top-level comma in an argument list) and returns it. The stop char is
+    /// not consumed. EOF outside of any pair also ends the scan.
+    /// Returns `ScanError::ExpectedClose` for a `close` without matching `open` (unless `close` is
+    /// a stop char) and for an `open` that is still unmatched at EOF.
+    pub fn scan_arg_until(&mut self, stop: &[char], open: char, close: char) -> Result<&'a str, ScanError> {
+        let start = self.offset();
+        let mut openers: Vec<Position> = Vec::new();
+        loop {
+            let at = self.position.clone();
+            match self.peek_next() {
+                Some(ch) if openers.is_empty() && stop.contains(&ch) => break,
+                Some(ch) if ch == open => openers.push(at),
+                Some(ch) if ch == close => {
+                    if openers.pop().is_none() {
+                        return Err(ScanError::ExpectedClose{ opener_at: None, found: Some(ch), at });
+                    }
+                },
+                Some(_) => {},
+                None => match openers.pop() {
+                    Some(opener_at) => return Err(ScanError::ExpectedClose{ opener_at: Some(opener_at), found: None, at }),
+                    None => break,
+                },
+            }
+            let _ = self.next();
+        }
+        Ok(&self.source[start..self.offset()])
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_balanced_respecting_strings() {
+        let mut text = Text::with_str("{ \"a}b\" {'\\'}'}\n} x");
+
+        let region = text.scan_balanced_respecting_strings('{', '}', &['"', '\''], '\\').unwrap();
+        assert_eq!(region.value, " \"a}b\" {'\\'}'}\n");
+        assert_eq!(region.span, Span::with(Position::with(1, 1), Position::with(2, 2)));
+        assert_eq!(text.next(), Some(' '));
+    }
+
+    #[test]
+    fn scan_balanced_respecting_strings_errors() {
+        let mut text = Text::with_str("{ \"}");
+        assert_eq!(text.scan_balanced_respecting_strings('{', '}', &['"'], '\\'),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 3), at: Position::with(1, 5) }));
+
+        let mut text = Text::with_str("x");
+        assert_eq!(text.scan_balanced_respecting_strings('{', '}', &['"'], '\\'),
+                   Err(ScanError::ExpectedChar{ expected: '{', found: Some('x'), at: Position::with(1, 1) }));
+    }
+
+    #[test]
+    fn scan_balanced_diagnosed() {
+        let mut text = Text::with_str("(a (b) c) d");
+
+        let region = text.scan_balanced_diagnosed('(', ')').unwrap();
+        assert_eq!(region.value, "a (b) c");
+        assert_eq!(region.span, Span::with(Position::with(1, 1), Position::with(1, 10)));
+    }
+
+    #[test]
+    fn scan_balanced_diagnosed_errors() {
+        let mut text = Text::with_str(") (a)");
+        assert_eq!(text.scan_balanced_diagnosed('(', ')'),
+                   Err(ScanError::ExpectedClose{ opener_at: None, found: Some(')'), at: Position::with(1, 1) }));
+
+        let mut text = Text::with_str("(a (b\n(c)");
+        assert_eq!(text.scan_balanced_diagnosed('(', ')'),
+                   Err(ScanError::ExpectedClose{ opener_at: Some(Position::with(1, 4)), found: None, at: Position::with(2, 4) }));
+    }
+
+    #[test]
+    fn scan_generic_args() {
+        let mut text = Text::with_str("<HashMap<K, Box<dyn Fn() -> V>>>>");
+
+        let args = text.scan_generic_args().unwrap();
+        assert_eq!(args.value, "HashMap<K, Box<dyn Fn() -> V>>");
+        assert_eq!(args.span, Span::with(Position::with(1, 1), Position::with(1, 33)));
+        assert_eq!(text.next(), Some('>'));
+    }
+
+    #[test]
+    fn scan_generic_args_unbalanced() {
+        let mut text = Text::with_str("<Vec<u8>");
+
+        assert_eq!(text.scan_generic_args(),
+                   Err(ScanError::ExpectedClose{ opener_at: Some(Position::with(1, 1)), found: None, at: Position::with(1, 9) }));
+    }
+
+    #[test]
+    fn scan_keyword_block() {
+        let mut text = Text::with_str("begin x; begin ending 1end end; end_x end.");
+
+        let block = text.scan_keyword_block("begin", "end").unwrap();
+        assert_eq!(block.value, " x; begin ending 1end end; end_x ");
+        assert_eq!(block.span, Span::with(Position::with(1, 1), Position::with(1, 42)));
+        assert_eq!(text.next(), Some('.'));
+    }
+
+    #[test]
+    fn scan_keyword_block_errors() {
+        let mut text = Text::with_str("begin\n  begin end\n");
+        assert_eq!(text.scan_keyword_block("begin", "end"),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(3, 1) }));
+
+        let mut text = Text::with_str("beginning end");
+        assert_eq!(text.scan_keyword_block("begin", "end"),
+                   Err(ScanError::Unexpected{ expected: "block", found: Some('b'), at: Position::with(1, 1) }));
+    }
+
+    #[test]
+    fn scan_arg_until() {
+        let mut text = Text::with_str("f(a, g(b, c)), d)");
+
+        assert_eq!(text.scan_arg_until(&[',', ')'], '(', ')'), Ok("f(a, g(b, c))"));
+        assert_eq!(text.next(), Some(','));
+        assert_eq!(text.scan_arg_until(&[',', ')'], '(', ')'), Ok(" d"));
+        assert_eq!(text.next(), Some(')'));
+    }
+
+    #[test]
+    fn scan_arg_until_unbalanced() {
+        let mut text = Text::with_str("f(a, (b)");
+        assert_eq!(text.scan_arg_until(&[','], '(', ')'),
+                   Err(ScanError::ExpectedClose{ opener_at: Some(Position::with(1, 2)), found: None, at: Position::with(1, 9) }));
+
+        let mut text = Text::with_str("a), b");
+        assert_eq!(text.scan_arg_until(&[','], '(', ')'),
+                   Err(ScanError::ExpectedClose{ opener_at: None, found: Some(')'), at: Position::with(1, 2) }));
+    }
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Position, ScanError, Text};
+
+/// Stateful checker for balanced brackets that keeps the positions of all open brackets, so that
+/// an unmatched bracket can be reported with the position where it was opened.
+#[derive(Clone, Debug)]
+pub struct BracketMatcher {
+    pairs: Vec<(char, char)>,
+    open: Vec<(char, Position)>,
+}
+
+impl Default for BracketMatcher {
+    fn default() -> Self {
+        BracketMatcher::new()
+    }
+}
+
+impl BracketMatcher {
+    /// Creates a `BracketMatcher` for `()`, `[]` and `{}`.
+    pub fn new() -> Self {
+        BracketMatcher::with_pairs(&[('(', ')'), ('[', ']'), ('{', '}')])
+    }
+
+    /// Creates a `BracketMatcher` for the given pairs of opening and closing brackets.
+    pub fn with_pairs(pairs: &[(char, char)]) -> Self {
+        BracketMatcher{ pairs: pairs.to_vec(), open: Vec::new() }
+    }
+
+    /// Returns the number of currently open brackets.
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Feeds the character `ch` found at position `at`. Characters that are no brackets are
+    /// ignored. Returns `ScanError::ExpectedClose` for a closing bracket that does not match the
+    /// most recently opened bracket.
+    pub fn feed(&mut self, ch: char, at: Position) -> Result<(), ScanError> {
+        if self.pairs.iter().any(|&(open, _)| open == ch) {
+            self.open.push((ch, at));
+        }
+        else if self.pairs.iter().any(|&(_, close)| close == ch) {
+            match self.open.pop() {
+                Some((open, opener_at)) if !self.pairs.contains(&(open, ch)) =>
+                    return Err(ScanError::ExpectedClose{ opener_at: Some(opener_at), found: Some(ch), at }),
+                Some(_) => {},
+                None => return Err(ScanError::ExpectedClose{ opener_at: None, found: Some(ch), at }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that all brackets have been closed at the end of the text (at `at`). Otherwise the
+    /// most recently opened bracket is reported.
+    pub fn finish(&self, at: Position) -> Result<(), ScanError> {
+        match self.open.last() {
+            Some((_, opener_at)) =>
+                Err(ScanError::ExpectedClose{ opener_at: Some(opener_at.clone()), found: None, at }),
+            None => Ok(()),
+        }
+    }
+
+    /// Feeds all remaining characters of `text` and checks that all brackets have been closed.
+    pub fn run(&mut self, text: &mut Text<'_>) -> Result<(), ScanError> {
+        loop {
+            let at = text.position.clone();
+            match text.next() {
+                Some(ch) => self.feed(ch, at)?,
+                None => return self.finish(at),
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn bracket_matcher_balanced() {
+        let mut text = Text::with_str("f(a[1], {b})\n");
+        let mut matcher = BracketMatcher::new();
+
+        assert_eq!(matcher.run(&mut text), Ok(()));
+        assert_eq!(matcher.depth(), 0);
+    }
+
+    #[test]
+    fn bracket_matcher_unmatched() {
+        let mut text = Text::with_str("f(a,\n  g(b)");
+        assert_eq!(BracketMatcher::new().run(&mut text),
+                   Err(ScanError::ExpectedClose{ opener_at: Some(Position::with(1, 2)), found: None,
+                                                 at: Position::with(2, 7) }));
+
+        let mut text = Text::with_str("(a]");
+        assert_eq!(BracketMatcher::new().run(&mut text),
+                   Err(ScanError::ExpectedClose{ opener_at: Some(Position::with(1, 1)), found: Some(']'),
+                                                 at: Position::with(1, 3) }));
+    }
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{is_line_terminator, Text};
+
+/// Coarse classification of a character, e.g. to select a sub-scanner in a lexer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharCategory {
+    /// alphabetic character
+    Letter,
+    /// ASCII digit `0` to `9`
+    Digit,
+    /// whitespace that does not terminate a line
+    Whitespace,
+    /// line terminator as classified by `is_line_terminator()`
+    Newline,
+    /// any other character
+    Symbol,
+    /// end of the text
+    Eof,
+}
+
+impl CharCategory {
+    /// Classifies the given character.
+    pub fn of(ch: char) -> Self {
+        if is_line_terminator(ch) {
+            CharCategory::Newline
+        }
+        else if ch.is_whitespace() {
+            CharCategory::Whitespace
+        }
+        else if ch.is_ascii_digit() {
+            CharCategory::Digit
+        }
+        else if ch.is_alphabetic() {
+            CharCategory::Letter
+        }
+        else {
+            CharCategory::Symbol
+        }
+    }
+}
+
+impl<'a> Text<'a> {
+    /// Returns the category of the next char without consuming it.
+    pub fn peek_category(&mut self) -> CharCategory {
+        self.peek_next().map_or(CharCategory::Eof, CharCategory::of)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn peek_category() {
+        let mut text = Text::with_str("\r7a \t+");
+
+        assert_eq!(text.peek_category(), CharCategory::Newline);
+        let _ = text.next();
+        assert_eq!(text.peek_category(), CharCategory::Digit);
+        assert_eq!(text.position(), &Position::with(2, 1));
+        let _ = text.next();
+        assert_eq!(text.peek_category(), CharCategory::Letter);
+        let _ = text.next();
+        assert_eq!(text.peek_category(), CharCategory::Whitespace);
+        let _ = text.next();
+        assert_eq!(text.peek_category(), CharCategory::Whitespace);
+        let _ = text.next();
+        assert_eq!(text.peek_category(), CharCategory::Symbol);
+        let _ = text.next();
+        assert_eq!(text.peek_category(), CharCategory::Eof);
+    }
+}
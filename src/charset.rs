@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::Text;
+
+/// Set of characters with a bitmap for fast lookup of ASCII characters and sorted ranges for
+/// all other characters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CharSet {
+    ascii: u128,
+    ranges: Vec<(char, char)>,
+}
+
+impl CharSet {
+    /// Creates an empty `CharSet`.
+    pub fn new() -> Self {
+        CharSet::default()
+    }
+
+    /// Creates a `CharSet` containing all the given inclusive ranges.
+    pub fn from_ranges(ranges: &[(char, char)]) -> Self {
+        let mut set = CharSet::new();
+        for &(lo, hi) in ranges {
+            set.add_range(lo, hi);
+        }
+        set
+    }
+
+    /// Adds all characters from `lo` up to (including) `hi`.
+    pub fn add_range(&mut self, lo: char, hi: char) {
+        for ch in lo..=hi.min('\u{7f}') {
+            self.ascii |= 1 << ch as u32;
+        }
+        let lo = lo.max('\u{80}');
+        if lo > hi {
+            return;
+        }
+
+        let index = self.ranges.partition_point(|&(start, _)| start < lo);
+        self.ranges.insert(index, (lo, hi));
+        let mut merged: Vec<(char, char)> = Vec::with_capacity(self.ranges.len());
+        for &(lo, hi) in &self.ranges {
+            match merged.last_mut() {
+                Some(last) if lo as u32 <= last.1 as u32 + 1 => last.1 = last.1.max(hi),
+                _ => merged.push((lo, hi)),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    /// Adds the character `ch`.
+    pub fn add_char(&mut self, ch: char) {
+        self.add_range(ch, ch);
+    }
+
+    /// Returns `true` if `ch` is in the set.
+    pub fn contains(&self, ch: char) -> bool {
+        if ch.is_ascii() {
+            return self.ascii & (1 << ch as u32) != 0;
+        }
+        self.ranges.binary_search_by(|&(lo, hi)| {
+            if hi < ch {
+                std::cmp::Ordering::Less
+            }
+            else if lo > ch {
+                std::cmp::Ordering::Greater
+            }
+            else {
+                std::cmp::Ordering::Equal
+            }
+        }).is_ok()
+    }
+}
+
+impl<'a> Text<'a> {
+    /// Consumes the longest run of characters contained in `set` and returns it.
+    pub fn scan_charset_run(&mut self, set: &CharSet) -> &'a str {
+        let rest = self.remaining();
+        let len = rest.char_indices()
+            .find(|&(_, ch)| !set.contains(ch))
+            .map_or(rest.len(), |(index, _)| index);
+        self.consume_bytes(len)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn charset_contains() {
+        let mut set = CharSet::from_ranges(&[('a', 'z'), ('0', '9'), ('α', 'γ')]);
+        set.add_char('_');
+        set.add_range('δ', 'ε');
+
+        assert!(set.contains('m') && set.contains('0') && set.contains('_'));
+        assert!(set.contains('β') && set.contains('ε'));
+        assert!(!set.contains('A') && !set.contains('-') && !set.contains('ζ'));
+        assert!(!CharSet::new().contains('a'));
+    }
+
+    #[test]
+    fn scan_charset_run() {
+        let set = CharSet::from_ranges(&[('a', 'z'), ('0', '9'), ('_', '_')]);
+        let mut text = Text::with_str("foo_bar1-baz");
+
+        assert_eq!(text.scan_charset_run(&set), "foo_bar1");
+        assert_eq!(text.position(), &Position::with(1, 9));
+        assert_eq!(text.scan_charset_run(&set), "");
+        assert_eq!(text.next(), Some('-'));
+    }
+}
@@ -0,0 +1,306 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{CharCategory, ScanError, Span, Spanned, Text};
+
+/// Result of `Text::scan_validated()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Validation<T> {
+    /// the scanned value was accepted and the scanned text is consumed
+    Accepted(T),
+    /// the scanned value was rejected and the reading position was reset
+    Rejected(T),
+}
+
+impl<T> Validation<T> {
+    /// Returns `true` for an accepted value.
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Validation::Accepted(_))
+    }
+
+    /// Returns the scanned value regardless of whether it was accepted.
+    pub fn into_inner(self) -> T {
+        match self {
+            Validation::Accepted(value) | Validation::Rejected(value) => value,
+        }
+    }
+}
+
+impl<'a> Text<'a> {
+    /// Runs `scan` and passes a successfully scanned value to `validate`. If `validate` returns
+    /// `false`, the reading position (and marker) is reset to where it was before `scan` and the
+    /// value is returned as `Validation::Rejected`. Errors of `scan` are returned unchanged
+    /// without resetting the reading position.
+    pub fn scan_validated<T, E>(&mut self, scan: impl FnOnce(&mut Text<'a>) -> Result<T, E>, validate: impl FnOnce(&T) -> bool) -> Result<Validation<T>, E> {
+        let checkpoint = self.clone();
+        let value = scan(self)?;
+        if validate(&value) {
+            Ok(Validation::Accepted(value))
+        }
+        else {
+            *self = checkpoint;
+            Ok(Validation::Rejected(value))
+        }
+    }
+
+    /// Applies `item` repeatedly until it returns `None` and returns the results together with the
+    /// span from the start of the first to the end of the last result (an empty span at the
+    /// reading position if there is none). Repetition also stops after a result for which `item`
+    /// did not consume anything.
+    pub fn scan_many<T>(&mut self, mut item: impl FnMut(&mut Text<'a>) -> Option<Spanned<T>>) -> (Vec<Spanned<T>>, Span) {
+        let mut items: Vec<Spanned<T>> = Vec::new();
+        let start = self.position.clone();
+        loop {
+            let offset = self.offset();
+            match item(self) {
+                Some(value) => items.push(value),
+                None => break,
+            }
+            if self.offset() == offset {
+                break;
+            }
+        }
+        let span = match (items.first(), items.last()) {
+            (Some(first), Some(last)) => Span::with(first.span.start.clone(), last.span.end.clone()),
+            _ => Span::empty_at(start),
+        };
+        (items, span)
+    }
+
+    /// Splits the whole text into the tokens returned by `scan`. Errors of `scan` are collected
+    /// and scanning continues until EOF; when a failing `scan` did not consume anything, one char
+    /// is skipped to make progress. Scanning stops early when `scan` succeeds without consuming
+    /// anything. Returns all tokens and all errors in the order of their appearance.
+    pub fn lex_recovering<T>(mut self, mut scan: impl FnMut(&mut Text<'a>) -> Result<Spanned<T>, ScanError>) -> (Vec<Spanned<T>>, Vec<ScanError>) {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        while self.peek_next().is_some() {
+            let offset = self.offset();
+            match scan(&mut self) {
+                Ok(token) => {
+                    tokens.push(token);
+                    if self.offset() == offset {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    errors.push(error);
+                    if self.offset() == offset {
+                        let _ = self.next();
+                    }
+                }
+            }
+        }
+        (tokens, errors)
+    }
+
+    /// Skips whitespace (including line terminators) and calls `on_letter`, `on_digit` or
+    /// `on_symbol` depending on the category of the next char (see `peek_category()`).
+    /// Returns `None` at EOF.
+    pub fn scan_dispatch<T>(&mut self, on_letter: impl FnOnce(&mut Text<'a>) -> Spanned<T>, on_digit: impl FnOnce(&mut Text<'a>) -> Spanned<T>, on_symbol: impl FnOnce(&mut Text<'a>) -> Spanned<T>) -> Option<Spanned<T>> {
+        self.skip_ws_counting_newlines();
+        match self.peek_category() {
+            CharCategory::Letter => Some(on_letter(self)),
+            CharCategory::Digit => Some(on_digit(self)),
+            CharCategory::Eof => None,
+            CharCategory::Symbol | CharCategory::Whitespace | CharCategory::Newline => Some(on_symbol(self)),
+        }
+    }
+
+    /// Scans a list of items enclosed by `open` and `close`, which must be next, and separated by
+    /// commas, e.g. `[1, 2, 3,]`. Whitespace (including line terminators) around the items is
+    /// skipped and a trailing comma is allowed. Errors of `item` are returned unchanged.
+    /// Returns `ScanError::ExpectedChar` if an item is not followed by a comma or `close` and
+    /// `ScanError::Unterminated` if the text ends before `close`.
+    pub fn scan_array<T>(&mut self, open: char, close: char, mut item: impl FnMut(&mut Text<'a>) -> Result<Spanned<T>, ScanError>) -> Result<Spanned<Vec<Spanned<T>>>, ScanError> {
+        let start = self.position.clone();
+        self.expect_char(open)?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_ws_counting_newlines();
+            match self.peek_next() {
+                Some(ch) if ch == close => break,
+                None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+                Some(_) => items.push(item(self)?),
+            }
+            self.skip_ws_counting_newlines();
+            match self.peek_next() {
+                Some(',') => { let _ = self.next(); },
+                Some(ch) if ch == close => break,
+                None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+                found => return Err(ScanError::ExpectedChar{ expected: ',', found, at: self.position.clone() }),
+            }
+        }
+        let _ = self.next();
+        Ok(self.spanned(start, items))
+    }
+
+    /// Passes the remaining text to `matcher`, which returns the length in bytes of the match
+    /// anchored at its start, and consumes the matched text. This allows to embed external
+    /// matchers while keeping the position tracking, e.g. for a `regex::Regex`:
+    ///
+    /// ```ignore
+    /// text.consume_match(|s| re.find(s).filter(|m| m.start() == 0).map(|m| m.end()))
+    /// ```
+    ///
+    /// Returns `None` without consuming anything if `matcher` returns `None` or an empty match.
+    ///
+    /// # Panics
+    /// Panics if the match length returned by `matcher` is not at a character boundary.
+    pub fn consume_match(&mut self, matcher: impl FnOnce(&'a str) -> Option<usize>) -> Option<Spanned<&'a str>> {
+        let rest = self.remaining();
+        let len = matcher(rest).filter(|&len| len > 0)?;
+        assert!(rest.is_char_boundary(len), "match length {} is not at a character boundary", len);
+        let start = self.position.clone();
+        let matched = self.consume_bytes(len);
+        Some(self.spanned(start, matched))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    fn identifier<'a>(text: &mut Text<'a>) -> Result<&'a str, ()> {
+        text.scan_ident_with(&[], &[]).map(|ident| ident.value).ok_or(())
+    }
+
+    #[test]
+    fn scan_validated() {
+        let mut text = Text::with_str("if x");
+
+        let result = text.scan_validated(identifier, |ident| *ident != "if");
+        assert_eq!(result, Ok(Validation::Rejected("if")));
+        assert_eq!(text.position(), &Position::with(1, 1));
+
+        assert_eq!(text.scan_validated(identifier, |_| true), Ok(Validation::Accepted("if")));
+        assert_eq!(text.position(), &Position::with(1, 3));
+        let _ = text.next();
+        let _ = text.next();
+        assert_eq!(text.scan_validated(identifier, |_| true), Err(()));
+    }
+
+    #[test]
+    fn scan_many() {
+        let mut text = Text::with_str("one two  three;");
+
+        let (words, span) = text.scan_many(|text| {
+            text.consume_while_on_line(|ch| ch == ' ');
+            text.scan_ident_with(&[], &[])
+        });
+        let words: Vec<_> = words.into_iter().map(|word| word.value).collect();
+        assert_eq!(words, vec!["one", "two", "three"]);
+        assert_eq!(span, Span::with(Position::with(1, 1), Position::with(1, 15)));
+        assert_eq!(text.next(), Some(';'));
+
+        let (words, span) = text.scan_many(|text| text.scan_ident_with(&[], &[]));
+        assert!(words.is_empty());
+        assert_eq!(span, Span::empty_at(Position::with(1, 16)));
+    }
+
+    #[test]
+    fn lex_recovering() {
+        let text = Text::with_str("12#");
+
+        let (tokens, errors) = text.lex_recovering(|text| {
+            let start = Text::position(text).clone();
+            let token = text.consume_between(1, 9, |ch| ch.is_ascii_digit())?;
+            Ok(Spanned::with(token, Span::with(start, Text::position(text).clone())))
+        });
+        assert_eq!(tokens, vec![Spanned::with("12", Span::with(Position::with(1, 1), Position::with(1, 3)))]);
+        assert_eq!(errors, vec![ScanError::TooFew{ at: Position::with(1, 3), needed: 1 }]);
+    }
+
+    #[test]
+    fn lex_recovering_without_progress() {
+        let text = Text::with_str("a b c");
+
+        let (tokens, errors) = text.lex_recovering(|text| Ok(Spanned::with((), Span::empty_at(Text::position(text).clone()))));
+        assert_eq!(tokens, vec![Spanned::with((), Span::empty_at(Position::with(1, 1)))]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn scan_dispatch() {
+        fn tagged<'a>(tag: &'static str) -> impl FnOnce(&mut Text<'a>) -> Spanned<&'static str> {
+            move |text| {
+                let start = Text::position(text).clone();
+                let _ = text.next();
+                Spanned::with(tag, Span::with(start, Text::position(text).clone()))
+            }
+        }
+        let mut text = Text::with_str("a 1\n +");
+
+        let token = text.scan_dispatch(tagged("letter"), tagged("digit"), tagged("symbol")).unwrap();
+        assert_eq!(token, Spanned::with("letter", Span::with(Position::with(1, 1), Position::with(1, 2))));
+        assert_eq!(text.scan_dispatch(tagged("letter"), tagged("digit"), tagged("symbol")).unwrap().value, "digit");
+        let token = text.scan_dispatch(tagged("letter"), tagged("digit"), tagged("symbol")).unwrap();
+        assert_eq!(token, Spanned::with("symbol", Span::with(Position::with(2, 2), Position::with(2, 3))));
+        assert_eq!(text.scan_dispatch(tagged("letter"), tagged("digit"), tagged("symbol")), None);
+    }
+
+    fn number<'a>(text: &mut Text<'a>) -> Result<Spanned<&'a str>, ScanError> {
+        let start = Text::position(text).clone();
+        let digits = text.consume_between(1, 9, |ch| ch.is_ascii_digit())?;
+        Ok(Spanned::with(digits, Span::with(start, Text::position(text).clone())))
+    }
+
+    #[test]
+    fn scan_array() {
+        let mut text = Text::with_str("[ ]");
+        let array = text.scan_array('[', ']', number).unwrap();
+        assert!(array.value.is_empty());
+        assert_eq!(array.span, Span::with(Position::with(1, 1), Position::with(1, 4)));
+
+        let mut text = Text::with_str("[\n  1,\n  23 ,\n]x");
+        let array = text.scan_array('[', ']', number).unwrap();
+        assert_eq!(array.value, vec![
+            Spanned::with("1", Span::with(Position::with(2, 3), Position::with(2, 4))),
+            Spanned::with("23", Span::with(Position::with(3, 3), Position::with(3, 5))),
+        ]);
+        assert_eq!(array.span, Span::with(Position::with(1, 1), Position::with(4, 2)));
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_array_errors() {
+        let mut text = Text::with_str("[1, 2\n");
+        assert_eq!(text.scan_array('[', ']', number),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(2, 1) }));
+
+        let mut text = Text::with_str("(1 2)");
+        assert_eq!(text.scan_array('(', ')', number),
+                   Err(ScanError::ExpectedChar{ expected: ',', found: Some('2'), at: Position::with(1, 4) }));
+
+        let mut text = Text::with_str("[1,,]");
+        assert_eq!(text.scan_array('[', ']', number), Err(ScanError::TooFew{ at: Position::with(1, 4), needed: 1 }));
+    }
+
+    #[test]
+    fn consume_match() {
+        let identifier = |s: &str| {
+            let len = s.find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_')).unwrap_or(s.len());
+            Some(len).filter(|_| s.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_'))
+        };
+        let mut text = Text::with_str("foo_1 2");
+
+        let matched = text.consume_match(identifier).unwrap();
+        assert_eq!(matched.value, "foo_1");
+        assert_eq!(matched.span, Span::with(Position::with(1, 1), Position::with(1, 6)));
+        assert_eq!(text.consume_match(identifier), None);
+        assert_eq!(text.next(), Some(' '));
+    }
+}
@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::lines::line_content;
+use crate::{is_line_terminator, ScanError, Spanned, Text};
+
+/// Style of a comment scanned by `Text::scan_comment()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommentStyle {
+    /// comment ending at the end of the line
+    Line,
+    /// comment ending with a closing marker
+    Block,
+}
+
+/// Comment scanned by `Text::scan_comment()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment<'a> {
+    /// whether this is a line or a block comment
+    pub style: CommentStyle,
+    /// content of the comment without its markers
+    pub body: &'a str,
+    /// `true` for a block comment without closing marker
+    pub unterminated: bool,
+}
+
+/// Comment classified by `Text::scan_comment_classified()` together with its body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommentKind<'a> {
+    /// `// ...` (including `//// ...`)
+    Line(&'a str),
+    /// `/// ...`
+    LineDoc(&'a str),
+    /// `/* ... */`
+    Block(&'a str),
+    /// `/** ... */`
+    BlockDoc(&'a str),
+}
+
+/// Returns the length in bytes of the body of the (possibly nested) block comment at the start
+/// of `s` and whether the closing `*/` was found. `s` starts after the opening marker.
+fn nested_block_len(s: &str) -> (usize, bool) {
+    let b = s.as_bytes();
+    let mut depth = 1;
+    let mut index = 0;
+    while index + 1 < b.len() {
+        match &b[index..index + 2] {
+            b"/*" => { depth += 1; index += 2; },
+            b"*/" => {
+                depth -= 1;
+                if depth == 0 {
+                    return (index, true);
+                }
+                index += 2;
+            },
+            _ => index += 1,
+        }
+    }
+    (s.len(), false)
+}
+
+/// Returns the length in bytes of the quoted span at the start of `s` (after the opening
+/// `quote`), which ends after the closing `quote` or before the end of the line. A backslash
+/// escapes the following char.
+fn comment_quote_len(s: &str, quote: char) -> usize {
+    let mut chars = s.char_indices();
+    while let Some((index, ch)) = chars.next() {
+        if ch == quote {
+            return index + ch.len_utf8();
+        }
+        if is_line_terminator(ch) {
+            return index;
+        }
+        if ch == '\\' && chars.clone().next().is_some_and(|(_, next)| !is_line_terminator(next)) {
+            let _ = chars.next();
+        }
+    }
+    s.len()
+}
+
+impl<'a> Text<'a> {
+    /// Scans a line comment starting with `line` or a block comment enclosed by `block` and
+    /// returns its body. Block comments are tried first, so that a block marker may start with
+    /// the line marker (e.g. `--` and `--[[`). The terminator of a line comment is not consumed.
+    /// An unterminated block comment extends to the end of the text.
+    /// Returns `None` without consuming anything when no comment starts at the reading position.
+    pub fn scan_comment(&mut self, line: Option<&str>, block: Option<(&str, &str)>) -> Option<Spanned<Comment<'a>>> {
+        let rest = self.remaining();
+        let start = self.position.clone();
+
+        if let Some((open, close)) = block.filter(|(open, _)| rest.starts_with(open)) {
+            let content = &rest[open.len()..];
+            let (body, unterminated, len) = match content.find(close) {
+                Some(index) => (&content[..index], false, open.len() + index + close.len()),
+                None => (content, true, rest.len()),
+            };
+            self.consume_bytes(len);
+            return Some(self.spanned(start, Comment{ style: CommentStyle::Block, body, unterminated }));
+        }
+
+        if let Some(marker) = line.filter(|marker| rest.starts_with(marker)) {
+            let content = &rest[marker.len()..];
+            let body = &content[..content.find(is_line_terminator).unwrap_or(content.len())];
+            self.consume_bytes(marker.len() + body.len());
+            return Some(self.spanned(start, Comment{ style: CommentStyle::Line, body, unterminated: false }));
+        }
+        None
+    }
+
+    /// Scans a Rust-style comment and classifies it as ordinary or doc comment by its marker:
+    /// `///` starts a line doc comment unless followed by another `/`, `/**` starts a block doc
+    /// comment unless followed by another `*` or by `/`. Block comments may be nested; an
+    /// unterminated block comment extends to the end of the text. The returned body excludes the
+    /// markers, the terminator of a line comment is not consumed.
+    /// Returns `None` without consuming anything when no comment starts at the reading position.
+    pub fn scan_comment_classified(&mut self) -> Option<Spanned<CommentKind<'a>>> {
+        let rest = self.remaining();
+        let start = self.position.clone();
+        if rest.starts_with("//") {
+            let doc = rest.starts_with("///") && !rest.starts_with("////");
+            let marker = if doc { 3 } else { 2 };
+            let body = line_content(&rest[marker..]);
+            self.consume_bytes(marker + body.len());
+            let kind = if doc { CommentKind::LineDoc(body) } else { CommentKind::Line(body) };
+            return Some(self.spanned(start, kind));
+        }
+        if rest.starts_with("/*") {
+            let doc = rest.starts_with("/**") && !rest.starts_with("/***") && !rest.starts_with("/**/");
+            let marker = if doc { 3 } else { 2 };
+            let (len, terminated) = nested_block_len(&rest[marker..]);
+            let body = &rest[marker..marker + len];
+            self.consume_bytes(marker + len + if terminated { 2 } else { 0 });
+            let kind = if doc { CommentKind::BlockDoc(body) } else { CommentKind::Block(body) };
+            return Some(self.spanned(start, kind));
+        }
+        None
+    }
+
+    /// Scans a block comment enclosed by `open` and `close`, which must be next, and returns its
+    /// body; the span covers the markers. With `nested` set, inner `open` markers must be closed
+    /// separately. Markers within a span quoted by one of `quotes` are ignored; such a span ends
+    /// at the closing quote (a backslash escapes the following char) or at the end of the line.
+    /// Returns `ScanError::Unterminated` (after consuming the rest of the text) if the comment is
+    /// not closed.
+    ///
+    /// # Panics
+    /// Panics if `open` or `close` is empty.
+    pub fn scan_block_comment_strings_aware(&mut self, open: &str, close: &str, nested: bool, quotes: &[char]) -> Result<Spanned<&'a str>, ScanError> {
+        assert!(!open.is_empty() && !close.is_empty(), "comment markers must not be empty");
+        let start = self.position.clone();
+        let rest = self.remaining();
+        if !rest.starts_with(open) {
+            return Err(ScanError::Unexpected{ expected: "comment", found: self.peek_next(), at: start });
+        }
+        let mut depth = 1;
+        let mut index = open.len();
+        while let Some(ch) = rest[index..].chars().next() {
+            if rest[index..].starts_with(close) {
+                depth -= 1;
+                index += close.len();
+                if depth == 0 {
+                    self.consume_bytes(index);
+                    return Ok(self.spanned(start, &rest[open.len()..index - close.len()]));
+                }
+            }
+            else if nested && rest[index..].starts_with(open) {
+                depth += 1;
+                index += open.len();
+            }
+            else if quotes.contains(&ch) {
+                index += ch.len_utf8();
+                index += comment_quote_len(&rest[index..], ch);
+            }
+            else {
+                index += ch.len_utf8();
+            }
+        }
+        self.consume_bytes(rest.len());
+        Err(ScanError::Unterminated{ start, at: self.position.clone() })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_line_comment() {
+        let mut text = Text::with_str("// note\nx");
+
+        let comment = text.scan_comment(Some("//"), Some(("/*", "*/"))).unwrap();
+        assert_eq!(comment.value, Comment{ style: CommentStyle::Line, body: " note", unterminated: false });
+        assert_eq!(comment.span, Span::with(Position::with(1, 1), Position::with(1, 8)));
+        assert_eq!(text.next(), Some('\n'));
+        assert_eq!(text.scan_comment(Some("//"), Some(("/*", "*/"))), None);
+    }
+
+    #[test]
+    fn scan_block_comment() {
+        let mut text = Text::with_str("/* a\n b */x");
+
+        let comment = text.scan_comment(Some("//"), Some(("/*", "*/"))).unwrap();
+        assert_eq!(comment.value, Comment{ style: CommentStyle::Block, body: " a\n b ", unterminated: false });
+        assert_eq!(comment.span, Span::with(Position::with(1, 1), Position::with(2, 6)));
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_block_comment_unterminated() {
+        let mut text = Text::with_str("/* a\n b");
+
+        let comment = text.scan_comment(None, Some(("/*", "*/"))).unwrap();
+        assert_eq!(comment.value, Comment{ style: CommentStyle::Block, body: " a\n b", unterminated: true });
+        assert_eq!(comment.span.end, Position::with(2, 3));
+    }
+
+    #[test]
+    fn scan_comment_classified() {
+        let mut text = Text::with_str("// a\n/// b\n//// c\n/* d /* e */ */\n/** f */\n/***/x");
+
+        let comment = text.scan_comment_classified().unwrap();
+        assert_eq!(comment.value, CommentKind::Line(" a"));
+        assert_eq!(comment.span, Span::with(Position::with(1, 1), Position::with(1, 5)));
+        let _ = text.next();
+        assert_eq!(text.scan_comment_classified().unwrap().value, CommentKind::LineDoc(" b"));
+        let _ = text.next();
+        assert_eq!(text.scan_comment_classified().unwrap().value, CommentKind::Line("// c"));
+        let _ = text.next();
+        assert_eq!(text.scan_comment_classified().unwrap().value, CommentKind::Block(" d /* e */ "));
+        let _ = text.next();
+        assert_eq!(text.scan_comment_classified().unwrap().value, CommentKind::BlockDoc(" f "));
+        let _ = text.next();
+        assert_eq!(text.scan_comment_classified().unwrap().value, CommentKind::Block("*"));
+        assert_eq!(text.scan_comment_classified(), None);
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_block_comment_strings_aware() {
+        let mut text = Text::with_str("/* see \"*/\" and '\\'*/' /* x */\n done */;");
+
+        let comment = text.scan_block_comment_strings_aware("/*", "*/", true, &['"', '\'']).unwrap();
+        assert_eq!(comment.value, " see \"*/\" and '\\'*/' /* x */\n done ");
+        assert_eq!(comment.span, Span::with(Position::with(1, 1), Position::with(2, 9)));
+        assert_eq!(text.next(), Some(';'));
+
+        let mut text = Text::with_str("(* a \"*)\" (* *)");
+        assert_eq!(text.scan_block_comment_strings_aware("(*", "*)", false, &['"']),
+                   Ok(Spanned::with(" a \"*)\" (* ", Span::with(Position::with(1, 1), Position::with(1, 16)))));
+    }
+
+    #[test]
+    fn scan_block_comment_strings_aware_unterminated() {
+        let mut text = Text::with_str("/* /* */");
+        assert_eq!(text.scan_block_comment_strings_aware("/*", "*/", true, &['"']),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(1, 9) }));
+    }
+
+    #[test]
+    #[should_panic(expected = "comment markers must not be empty")]
+    fn scan_block_comment_strings_aware_empty_marker() {
+        let _ = Text::with_str("abc */").scan_block_comment_strings_aware("", "*/", true, &[]);
+    }
+}
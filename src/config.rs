@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ident::ident_len;
+use crate::lines::{indent_width, is_blank, line_content};
+use crate::{Counter, ScanError, Spanned, Text};
+
+/// Segment of a key path as returned by `Text::scan_key_path()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathSeg<'a> {
+    /// named field, e.g. `name` in `servers[0].name`
+    Field(&'a str),
+    /// index, e.g. `0` in `servers[0].name`
+    Index(u64),
+}
+
+/// Key and value of a mapping line as returned by `Text::scan_mapping()`.
+pub type MappingEntry<'a> = (Spanned<&'a str>, Spanned<&'a str>);
+
+impl<'a> Text<'a> {
+    /// Scans an identifier or returns `ScanError::Unexpected` if there is none.
+    pub(crate) fn expect_ident(&mut self) -> Result<&'a str, ScanError> {
+        match ident_len(self.remaining(), &[], &[]) {
+            0 => Err(ScanError::Unexpected{ expected: "identifier", found: self.peek_next(),
+                                            at: self.position.clone() }),
+            len => Ok(self.consume_bytes(len)),
+        }
+    }
+
+    /// Scans a key that is either a bare identifier or a string quoted by `"` or `'` and returns
+    /// the key's text. Quoted keys support the escapes `\\`, `\"`, `\'`, `\n`, `\r`, `\t`, `\0`,
+    /// `\uXXXX` and `\UXXXXXXXX` and must not span lines.
+    pub fn scan_key(&mut self) -> Result<Spanned<String>, ScanError> {
+        if let Some('"' | '\'') = self.peek_next() {
+            return self.scan_escaped_string();
+        }
+        match ident_len(self.remaining(), &[], &[]) {
+            0 => Err(ScanError::Unexpected{ expected: "key", found: self.peek_next(), at: self.position.clone() }),
+            len => {
+                let start = self.position.clone();
+                let key = self.consume_bytes(len).to_string();
+                Ok(self.spanned(start, key))
+            },
+        }
+    }
+
+    /// Scans a key path like `servers[0].name` consisting of identifiers separated by `.` and of
+    /// bracketed integer indices.
+    pub fn scan_key_path(&mut self) -> Result<Spanned<Vec<PathSeg<'a>>>, ScanError> {
+        let start = self.position.clone();
+        let mut segments = vec![PathSeg::Field(self.expect_ident()?)];
+        loop {
+            match self.peek_next() {
+                Some('.') => {
+                    let _ = self.next();
+                    segments.push(PathSeg::Field(self.expect_ident()?));
+                },
+                Some('[') => {
+                    let open_at = self.position.clone();
+                    let _ = self.next();
+                    let rest = self.remaining();
+                    let len = rest.bytes().take_while(u8::is_ascii_digit).count();
+                    if len == 0 {
+                        return Err(match self.peek_next() {
+                            None => ScanError::Unterminated{ start: open_at, at: self.position.clone() },
+                            found => ScanError::Unexpected{ expected: "index", found, at: self.position.clone() },
+                        });
+                    }
+                    let index = rest[..len].parse()
+                        .map_err(|_| ScanError::Overflow{ at: self.position.clone() })?;
+                    self.consume_bytes(len);
+                    if self.peek_next().is_none() {
+                        return Err(ScanError::Unterminated{ start: open_at, at: self.position.clone() });
+                    }
+                    self.expect_char(']')?;
+                    segments.push(PathSeg::Index(index));
+                },
+                _ => break,
+            }
+        }
+        Ok(self.spanned(start, segments))
+    }
+
+    /// Scans a block of `key<sep>value` lines indented by `indent` columns and returns the
+    /// trimmed keys and values. Blank lines are skipped; the block ends at EOF or before the first
+    /// less indented line. Returns `ScanError::Unexpected` for a more indented line and
+    /// `ScanError::ExpectedChar` (reported at the end of the line) for a line without `sep`;
+    /// in both cases the reading position is left at the start of the offending line.
+    pub fn scan_mapping(&mut self, indent: Counter, sep: char) -> Result<Vec<MappingEntry<'a>>, ScanError> {
+        let mut entries = Vec::new();
+        while !self.remaining().is_empty() {
+            let line = line_content(self.remaining());
+            if is_blank(line) {
+                self.take_line();
+                continue;
+            }
+            let content = line.trim_start();
+            let line_indent = indent_width(line, self.tab_width);
+            if line_indent < indent {
+                break;
+            }
+            if line_indent > indent {
+                return Err(ScanError::Unexpected{ expected: "key", found: content.chars().next(),
+                                                  at: self.position_after(line.len() - content.len()) });
+            }
+            let Some(sep_pos) = content.find(sep) else {
+                let found = self.remaining()[line.len()..].chars().next();
+                return Err(ScanError::ExpectedChar{ expected: sep, found, at: self.position_after(line.len()) });
+            };
+            self.consume_bytes(line.len() - content.len());
+            let key_start = self.position.clone();
+            let key = content[..sep_pos].trim_end();
+            self.consume_bytes(key.len());
+            let key = self.spanned(key_start, key);
+
+            let rest = &content[sep_pos + sep.len_utf8()..];
+            let value = rest.trim();
+            self.consume_bytes(sep_pos - key.value.len() + sep.len_utf8() + rest.len() - rest.trim_start().len());
+            let value_start = self.position.clone();
+            self.consume_bytes(value.len());
+            let value = self.spanned(value_start, value);
+            self.take_line();
+            entries.push((key, value));
+        }
+        Ok(entries)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_key() {
+        let mut text = Text::with_str("name = \"a \\\"b\\\"\\u00e9\" = 'c'");
+
+        let key = text.scan_key().unwrap();
+        assert_eq!(key.value, "name");
+        assert_eq!(key.span, Span::with(Position::with(1, 1), Position::with(1, 5)));
+        text.consume_bytes(3);
+        let key = text.scan_key().unwrap();
+        assert_eq!(key.value, "a \"b\"é");
+        assert_eq!(key.span, Span::with(Position::with(1, 8), Position::with(1, 23)));
+        text.consume_bytes(3);
+        assert_eq!(text.scan_key().unwrap().value, "c");
+    }
+
+    #[test]
+    fn scan_key_errors() {
+        let mut text = Text::with_str("\"abc\n\"");
+        assert_eq!(text.scan_key(), Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(1, 5) }));
+
+        let mut text = Text::with_str("'a\\x'");
+        assert_eq!(text.scan_key(), Err(ScanError::InvalidEscape{ at: Position::with(1, 3) }));
+
+        let mut text = Text::with_str("= 1");
+        assert_eq!(text.scan_key(), Err(ScanError::Unexpected{ expected: "key", found: Some('='), at: Position::with(1, 1) }));
+    }
+
+    #[test]
+    fn scan_key_path() {
+        let mut text = Text::with_str("a.b[2].c = 1");
+
+        let path = text.scan_key_path().unwrap();
+        assert_eq!(path.value, vec![PathSeg::Field("a"), PathSeg::Field("b"), PathSeg::Index(2), PathSeg::Field("c")]);
+        assert_eq!(path.span, Span::with(Position::with(1, 1), Position::with(1, 9)));
+        assert_eq!(text.next(), Some(' '));
+    }
+
+    #[test]
+    fn scan_mapping() {
+        let mut text = Text::with_str("  name: demo\n\n  port : 80 \nnext: 1");
+
+        let entries = text.scan_mapping(2, ':').unwrap();
+        assert_eq!(entries, vec![
+            (Spanned::with("name", Span::with(Position::with(1, 3), Position::with(1, 7))),
+             Spanned::with("demo", Span::with(Position::with(1, 9), Position::with(1, 13)))),
+            (Spanned::with("port", Span::with(Position::with(3, 3), Position::with(3, 7))),
+             Spanned::with("80", Span::with(Position::with(3, 10), Position::with(3, 12)))),
+        ]);
+        assert_eq!(text.position(), &Position::with(4, 1));
+    }
+
+    #[test]
+    fn scan_mapping_malformed() {
+        let mut text = Text::with_str("a: 1\nb 2\n");
+
+        assert_eq!(text.scan_mapping(0, ':'),
+                   Err(ScanError::ExpectedChar{ expected: ':', found: Some('\n'), at: Position::with(2, 4) }));
+        assert_eq!(text.position(), &Position::with(2, 1));
+
+        let mut text = Text::with_str("a: 1\n  b: 2\nc: 3\n");
+        assert_eq!(text.scan_mapping(0, ':'),
+                   Err(ScanError::Unexpected{ expected: "key", found: Some('b'), at: Position::with(2, 3) }));
+        assert_eq!(text.position(), &Position::with(2, 1));
+
+        let mut text = Text::with_str("  a: 1\nb: 2\n");
+        assert_eq!(text.scan_mapping(2, ':').unwrap().len(), 1);
+        assert_eq!(text.remaining(), "b: 2\n");
+    }
+
+    #[test]
+    fn scan_key_path_errors() {
+        let mut text = Text::with_str("a[2");
+        assert_eq!(text.scan_key_path(),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 2), at: Position::with(1, 4) }));
+
+        let mut text = Text::with_str("a[x]");
+        assert_eq!(text.scan_key_path(),
+                   Err(ScanError::Unexpected{ expected: "index", found: Some('x'), at: Position::with(1, 3) }));
+
+        let mut text = Text::with_str("a.[0]");
+        assert_eq!(text.scan_key_path(),
+                   Err(ScanError::Unexpected{ expected: "identifier", found: Some('['), at: Position::with(1, 3) }));
+    }
+}
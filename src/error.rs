@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use crate::Position;
+
+/// Errors reported by the scanning functions of `Text`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScanError {
+    /// A construct starting at `start` was not terminated before the end of the text at `at`.
+    Unterminated { start: Position, at: Position },
+    /// `needed` digits were expected at `at`, but fewer were found.
+    ExpectedDigits { at: Position, needed: usize },
+    /// The number starting at `at` does not fit into the result type.
+    Overflow { at: Position },
+    /// A closing bracket for the bracket opened at `opener_at` was expected at `at`, but `found`
+    /// (`None` for EOF) was encountered. `opener_at` is `None` for a closing bracket without
+    /// matching opening bracket.
+    ExpectedClose { opener_at: Option<Position>, found: Option<char>, at: Position },
+    /// `expected` was expected at `at`, but `found` (`None` for EOF) was encountered.
+    ExpectedChar { expected: char, found: Option<char>, at: Position },
+    /// A token described by `expected` was expected at `at`, but `found` (`None` for EOF) was
+    /// encountered.
+    Unexpected { expected: &'static str, found: Option<char>, at: Position },
+    /// The indentation at `at` does not match any enclosing indentation level.
+    InconsistentDedent { at: Position },
+    /// At least `needed` matching characters were expected at `at`.
+    TooFew { at: Position, needed: usize },
+    /// A single space was expected at `at`.
+    ExpectedSpace { at: Position },
+    /// The digit group starting at `at` has the wrong number of digits.
+    BadGrouping { at: Position },
+    /// The escape sequence starting at `at` is invalid.
+    InvalidEscape { at: Position },
+    /// The named entity starting at `at` is unknown.
+    UnknownEntity { at: Position },
+    /// `value` specified at `at` is no Unicode scalar value.
+    InvalidCodePoint { value: u32, at: Position },
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::Unterminated { start, at } =>
+                write!(f, "{}: unterminated construct starting at {}", at, start),
+            ScanError::ExpectedDigits { at, needed } =>
+                write!(f, "{}: expected {} digits", at, needed),
+            ScanError::Overflow { at } =>
+                write!(f, "{}: number too large", at),
+            ScanError::ExpectedClose { opener_at: None, found, at } =>
+                write!(f, "{}: unexpected {}", at, describe(found)),
+            ScanError::ExpectedClose { opener_at: Some(opener_at), found, at } =>
+                write!(f, "{}: unexpected {}, expected closing bracket for bracket at {}", at, describe(found), opener_at),
+            ScanError::ExpectedChar { expected, found, at } =>
+                write!(f, "{}: expected {:?}, found {}", at, expected, describe(found)),
+            ScanError::Unexpected { expected, found, at } =>
+                write!(f, "{}: expected {}, found {}", at, expected, describe(found)),
+            ScanError::InconsistentDedent { at } =>
+                write!(f, "{}: dedent does not match any outer indentation level", at),
+            ScanError::TooFew { at, needed } =>
+                write!(f, "{}: expected at least {} matching characters", at, needed),
+            ScanError::ExpectedSpace { at } =>
+                write!(f, "{}: expected a single space", at),
+            ScanError::BadGrouping { at } =>
+                write!(f, "{}: digit group has wrong size", at),
+            ScanError::InvalidEscape { at } =>
+                write!(f, "{}: invalid escape sequence", at),
+            ScanError::UnknownEntity { at } =>
+                write!(f, "{}: unknown entity", at),
+            ScanError::InvalidCodePoint { value, at } =>
+                write!(f, "{}: invalid code point U+{:04X}", at, value),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// Describes a found character (or EOF for `None`) in an error message.
+fn describe(found: &Option<char>) -> String {
+    match found {
+        Some(ch) => format!("{:?}", ch),
+        None => "end of text".to_string(),
+    }
+}
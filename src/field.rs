@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{is_line_terminator, Counter, Span, Spanned, Text};
+
+/// Reason why `Text::consume_until_escaped_limited()` stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// the stop predicate holds for the next char
+    Predicate,
+    /// the text ends directly after an escape char
+    EscapeAtEof,
+    /// the maximum number of chars has been consumed
+    LimitReached,
+    /// the end of the text has been reached
+    Eof,
+}
+
+impl<'a> Text<'a> {
+    /// Scans a field of at most `width` characters that may end early at `delim` or at a line
+    /// terminator. The delimiter resp. line terminator is not consumed.
+    pub fn scan_field_fixed_or_delim(&mut self, width: Counter, delim: char) -> &'a str {
+        let start = self.offset();
+        let end_column = self.position.column.saturating_add(width);
+        while self.position.column < end_column {
+            match self.peek_next() {
+                Some(ch) if ch != delim && !is_line_terminator(ch) => { let _ = self.next(); },
+                _ => break,
+            }
+        }
+        &self.source[start..self.offset()]
+    }
+
+    /// Scans a list of fields separated by `sep` up to the end of the line (the line terminator is
+    /// not consumed) and returns the fields without surrounding whitespace together with the spans
+    /// of the separators. There is always at least one (possibly empty) field.
+    pub fn scan_separated_with_sep_spans(&mut self, sep: char) -> (Vec<Spanned<&'a str>>, Vec<Span>) {
+        let mut fields = Vec::new();
+        let mut seps = Vec::new();
+        loop {
+            let rest = self.remaining();
+            let raw = &rest[..rest.find(|ch| ch == sep || is_line_terminator(ch)).unwrap_or(rest.len())];
+            let field = raw.trim();
+            self.consume_bytes(raw.len() - raw.trim_start().len());
+            let start = self.position.clone();
+            self.consume_bytes(field.len());
+            fields.push(self.spanned(start, field));
+            self.consume_bytes(raw.trim_start().len() - field.len());
+
+            if self.peek_next() != Some(sep) {
+                break;
+            }
+            let start = self.position.clone();
+            let _ = self.next();
+            seps.push(Span::with(start, self.position.clone()));
+        }
+        (fields, seps)
+    }
+
+    /// Consumes at most `max` characters until `stop` holds for the next char. The `escape` char
+    /// and the char following it are always consumed together, which lets an escaped char pass
+    /// `stop`. The char that satisfies `stop` is not consumed.
+    /// Returns the consumed slice (with escape chars kept) and the reason for stopping.
+    pub fn consume_until_escaped_limited<F: Fn(char) -> bool>(&mut self, stop: F, escape: char, max: usize) -> (&'a str, StopReason) {
+        let start = self.offset();
+        let mut count = 0;
+        let reason = loop {
+            let ch = match self.peek_next() {
+                Some(ch) => ch,
+                None => break StopReason::Eof,
+            };
+            if ch == escape {
+                let at_eof = self.remaining()[ch.len_utf8()..].is_empty();
+                if count + if at_eof { 1 } else { 2 } > max {
+                    break StopReason::LimitReached;
+                }
+                let _ = self.next();
+                if self.next().is_none() {
+                    break StopReason::EscapeAtEof;
+                }
+                count += 2;
+                continue;
+            }
+            if stop(ch) {
+                break StopReason::Predicate;
+            }
+            if count >= max {
+                break StopReason::LimitReached;
+            }
+            let _ = self.next();
+            count += 1;
+        };
+        (&self.source[start..self.offset()], reason)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_field_fixed_width() {
+        let mut text = Text::with_str("abcdefgh|ij");
+
+        assert_eq!(text.scan_field_fixed_or_delim(3, '|'), "abc");
+        assert_eq!(text.scan_field_fixed_or_delim(3, '|'), "def");
+        assert_eq!(text.position(), &Position::with(1, 7));
+    }
+
+    #[test]
+    fn scan_field_fixed_delimited() {
+        let mut text = Text::with_str("ab|cd\nef");
+
+        assert_eq!(text.scan_field_fixed_or_delim(4, '|'), "ab");
+        assert_eq!(text.next(), Some('|'));
+        assert_eq!(text.scan_field_fixed_or_delim(4, '|'), "cd");
+        assert_eq!(text.peek_next(), Some('\n'));
+        let _ = text.next();
+        assert_eq!(text.scan_field_fixed_or_delim(Counter::MAX, '|'), "ef");
+    }
+
+    #[test]
+    fn scan_separated_with_sep_spans() {
+        let mut text = Text::with_str("a, b ,c\nd");
+
+        let (fields, seps) = text.scan_separated_with_sep_spans(',');
+        assert_eq!(fields, vec![
+            Spanned::with("a", Span::with(Position::with(1, 1), Position::with(1, 2))),
+            Spanned::with("b", Span::with(Position::with(1, 4), Position::with(1, 5))),
+            Spanned::with("c", Span::with(Position::with(1, 7), Position::with(1, 8))),
+        ]);
+        assert_eq!(seps, vec![
+            Span::with(Position::with(1, 2), Position::with(1, 3)),
+            Span::with(Position::with(1, 6), Position::with(1, 7)),
+        ]);
+        assert_eq!(text.peek_next(), Some('\n'));
+    }
+
+    #[test]
+    fn consume_until_escaped_limited() {
+        let mut text = Text::with_str("a\\,b,c");
+        assert_eq!(text.consume_until_escaped_limited(|ch| ch == ',', '\\', 10), ("a\\,b", StopReason::Predicate));
+        assert_eq!(text.position(), &Position::with(1, 5));
+
+        let mut text = Text::with_str("abcdef");
+        assert_eq!(text.consume_until_escaped_limited(|ch| ch == ',', '\\', 4), ("abcd", StopReason::LimitReached));
+
+        let mut text = Text::with_str("ab\\");
+        assert_eq!(text.consume_until_escaped_limited(|ch| ch == ',', '\\', 10), ("ab\\", StopReason::EscapeAtEof));
+        let mut text = Text::with_str("ab\\");
+        assert_eq!(text.consume_until_escaped_limited(|ch| ch == ',', '\\', 3), ("ab\\", StopReason::EscapeAtEof));
+
+        let mut text = Text::with_str("a\nb");
+        assert_eq!(text.consume_until_escaped_limited(|ch| ch == ',', '\\', 10), ("a\nb", StopReason::Eof));
+        assert_eq!(text.position(), &Position::with(2, 2));
+    }
+}
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Spanned, Text};
+
+/// Returns the length in bytes of the identifier at the start of `s` or 0 if `s` does not start
+/// with an identifier. An identifier starts with an alphabetic char, `_` or one of `start_extra`
+/// and continues with alphanumeric chars, `_` or `cont_extra`.
+pub(crate) fn ident_len(s: &str, start_extra: &[char], cont_extra: &[char]) -> usize {
+    let mut chars = s.char_indices();
+    match chars.next() {
+        Some((_, ch)) if ch.is_alphabetic() || ch == '_' || start_extra.contains(&ch) => {},
+        _ => return 0,
+    }
+    chars.find(|&(_, ch)| !(ch.is_alphanumeric() || ch == '_' || cont_extra.contains(&ch)))
+        .map_or(s.len(), |(index, _)| index)
+}
+
+/// Returns `true` if `s` starts with one of `keywords` (ignoring ASCII case) that is not
+/// directly followed by an identifier char.
+pub(crate) fn starts_with_keyword(s: &str, keywords: &[&str]) -> bool {
+    keywords.iter().any(|keyword| {
+        s.get(..keyword.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(keyword))
+            && !s[keyword.len()..].starts_with(|ch: char| ch.is_alphanumeric() || ch == '_')
+    })
+}
+
+impl<'a> Text<'a> {
+    /// Scans an identifier that starts with an alphabetic char, `_` or one of `start_extra` and
+    /// continues with alphanumeric chars, `_` or one of `cont_extra`.
+    /// Returns `None` without consuming anything if no identifier starts at the reading position.
+    pub fn scan_ident_with(&mut self, start_extra: &[char], cont_extra: &[char]) -> Option<Spanned<&'a str>> {
+        let len = ident_len(self.remaining(), start_extra, cont_extra);
+        if len == 0 {
+            return None;
+        }
+        let start = self.position.clone();
+        let ident = self.consume_bytes(len);
+        Some(self.spanned(start, ident))
+    }
+
+    /// Scans a token like `@mention` or `#tag` consisting of `sigil` and at least one following
+    /// char for which `body` holds. Returns the whole token including the sigil.
+    /// Returns `None` without consuming anything if the next char is not `sigil` or if it is not
+    /// followed by a body char.
+    pub fn scan_sigil_token(&mut self, sigil: char, body: impl Fn(char) -> bool) -> Option<Spanned<&'a str>> {
+        let rest = self.remaining().strip_prefix(sigil)?;
+        let len = rest.find(|ch: char| !body(ch)).unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        let start = self.position.clone();
+        let token = self.consume_bytes(sigil.len_utf8() + len);
+        Some(self.spanned(start, token))
+    }
+
+    /// Scans a label, i.e. an identifier immediately followed by a single `:`, and returns the
+    /// identifier. The span covers the label including the colon.
+    /// Returns `None` without consuming anything if there is no identifier or if it is not
+    /// followed by a single colon, so `a::b` is not scanned as label `a`.
+    pub fn scan_label(&mut self) -> Option<Spanned<&'a str>> {
+        let rest = self.remaining();
+        let len = ident_len(rest, &[], &[]);
+        if len == 0 || !rest[len..].starts_with(':') || rest[len..].starts_with("::") {
+            return None;
+        }
+
+        let start = self.position.clone();
+        let label = self.consume_bytes(len);
+        let _ = self.next();
+        Some(self.spanned(start, label))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_ident_with() {
+        let mut text = Text::with_str("foo-bar valid? $x -y");
+
+        let ident = text.scan_ident_with(&[], &['-']).unwrap();
+        assert_eq!(ident.value, "foo-bar");
+        assert_eq!(ident.span, Span::with(Position::with(1, 1), Position::with(1, 8)));
+        let _ = text.next();
+        assert_eq!(text.scan_ident_with(&[], &['?', '!']).unwrap().value, "valid?");
+        let _ = text.next();
+        assert_eq!(text.scan_ident_with(&['$'], &['$']).unwrap().value, "$x");
+        let _ = text.next();
+        assert_eq!(text.scan_ident_with(&[], &['-']), None);
+    }
+
+    #[test]
+    fn scan_sigil_token() {
+        let word = |ch: char| ch.is_alphanumeric() || ch == '_';
+        let mut text = Text::with_str("@alice #tag @ x");
+
+        let mention = text.scan_sigil_token('@', word).unwrap();
+        assert_eq!(mention.value, "@alice");
+        assert_eq!(mention.span, Span::with(Position::with(1, 1), Position::with(1, 7)));
+        let _ = text.next();
+        assert_eq!(text.scan_sigil_token('#', word).unwrap().value, "#tag");
+        let _ = text.next();
+        assert_eq!(text.scan_sigil_token('@', word), None);
+        assert_eq!(text.next(), Some('@'));
+    }
+
+    #[test]
+    fn scan_label() {
+        let mut text = Text::with_str("loop: x");
+
+        let label = text.scan_label().unwrap();
+        assert_eq!(label.value, "loop");
+        assert_eq!(label.span, Span::with(Position::with(1, 1), Position::with(1, 6)));
+        assert_eq!(text.next(), Some(' '));
+    }
+
+    #[test]
+    fn scan_label_rejected() {
+        let mut text = Text::with_str("a::b");
+        assert_eq!(text.scan_label(), None);
+        assert_eq!(text.next(), Some('a'));
+
+        let mut text = Text::with_str("1:");
+        assert_eq!(text.scan_label(), None);
+        assert_eq!(text.next(), Some('1'));
+    }
+}
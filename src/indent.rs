@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::lines::{indent_width, is_blank, line_content};
+use crate::{Counter, ScanError, Spanned, Text};
+
+/// Change of the indentation between two lines as reported by `IndentTracker`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndentEvent {
+    /// the line is indented deeper than the previous one
+    Indent,
+    /// the line closes the given number of indentation levels
+    Dedent(usize),
+    /// the line has the same indentation as the previous one
+    Same,
+}
+
+/// Tracks the indentation levels of consecutive lines for off-side rule grammars.
+#[derive(Clone, Debug)]
+pub struct IndentTracker {
+    levels: Vec<Counter>,
+}
+
+impl Default for IndentTracker {
+    fn default() -> Self {
+        IndentTracker::new()
+    }
+}
+
+impl IndentTracker {
+    /// Creates a new `IndentTracker` at indentation 0.
+    pub fn new() -> Self {
+        IndentTracker{ levels: vec![0] }
+    }
+
+    /// Returns the number of currently open indentation levels.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Skips blank lines, consumes the indentation of the next line and compares it with the
+    /// indentation levels of the previous lines (tabs are expanded to `text.tab_width()`).
+    /// The span of the event covers the consumed indentation. The reading position must be at
+    /// the start of a line; the caller consumes the rest of the line.
+    /// Returns `None` at EOF and `ScanError::InconsistentDedent` for a dedent to a width that does
+    /// not match an outer level.
+    pub fn next_line(&mut self, text: &mut Text<'_>) -> Option<Result<Spanned<IndentEvent>, ScanError>> {
+        while is_blank(line_content(text.remaining())) {
+            if text.remaining().is_empty() {
+                return None;
+            }
+            text.take_line();
+        }
+
+        let start = text.position.clone();
+        let line = text.remaining();
+        let width = indent_width(line, text.tab_width);
+        text.consume_bytes(line.len() - line.trim_start_matches([' ', '\t']).len());
+
+        let current = *self.levels.last().unwrap();
+        let event = if width > current {
+            self.levels.push(width);
+            IndentEvent::Indent
+        }
+        else if width == current {
+            IndentEvent::Same
+        }
+        else {
+            match self.levels.iter().rposition(|&level| level == width) {
+                Some(index) => {
+                    let count = self.levels.len() - 1 - index;
+                    self.levels.truncate(index + 1);
+                    IndentEvent::Dedent(count)
+                },
+                None => return Some(Err(ScanError::InconsistentDedent{ at: text.position.clone() })),
+            }
+        };
+        Some(Ok(text.spanned(start, event)))
+    }
+
+    /// Closes all open indentation levels at the end of the text and returns their number.
+    pub fn finish(&mut self) -> usize {
+        let count = self.depth();
+        self.levels.truncate(1);
+        count
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    fn events(source: &str) -> Vec<Result<IndentEvent, ScanError>> {
+        let mut text = Text::with_str(source);
+        let mut tracker = IndentTracker::new();
+        let mut events = Vec::new();
+        while let Some(event) = tracker.next_line(&mut text) {
+            let failed = event.is_err();
+            events.push(event.map(|event| event.value));
+            if failed {
+                break;
+            }
+            let _ = text.consume_while_on_line(|_| true);
+        }
+        events
+    }
+
+    #[test]
+    fn indent_tracker() {
+        assert_eq!(events("a\n  b\n\n  c\n    d\ne\n"), vec![
+            Ok(IndentEvent::Same), Ok(IndentEvent::Indent), Ok(IndentEvent::Same),
+            Ok(IndentEvent::Indent), Ok(IndentEvent::Dedent(2)),
+        ]);
+
+        let mut text = Text::with_str("a\n\tb");
+        let mut tracker = IndentTracker::new();
+        let _ = tracker.next_line(&mut text);
+        let _ = text.consume_while_on_line(|_| true);
+        let event = tracker.next_line(&mut text).unwrap().unwrap();
+        assert_eq!(event.span, Span::with(Position::with(2, 1), Position::with(2, 2)));
+        assert_eq!(tracker.finish(), 1);
+    }
+
+    #[test]
+    fn indent_tracker_mismatched_dedent() {
+        let result = events("a\n    b\n  c\n");
+        assert_eq!(result.last(), Some(&Err(ScanError::InconsistentDedent{ at: Position::with(3, 3) })));
+    }
+}
@@ -12,11 +12,84 @@
 // You should have received a copy of the GNU General Public License along with Foobar.
 // If not, see <https://www.gnu.org/licenses/>.
 
+use std::fmt;
 use std::str::Chars;
 
+mod ansi;
+mod balanced;
+mod bracket;
+mod category;
+mod charset;
+mod combinator;
+mod comment;
+mod config;
+mod error;
+mod field;
+mod ident;
+mod indent;
+mod lines;
+mod literal;
+mod markup;
+mod number;
+mod operator;
+mod run;
+mod shell;
+mod span;
+mod sql;
+mod string;
+mod template;
+mod time;
+mod trivia;
+mod uri;
+mod whitespace;
+mod width;
+mod words;
+
+pub use bracket::BracketMatcher;
+pub use category::CharCategory;
+pub use charset::CharSet;
+pub use combinator::Validation;
+pub use comment::{Comment, CommentKind, CommentStyle};
+pub use config::{MappingEntry, PathSeg};
+pub use error::ScanError;
+pub use field::StopReason;
+pub use indent::{IndentEvent, IndentTracker};
+pub use lines::LineEnding;
+pub use markup::FencedBlock;
+pub use number::{FloatValue, Interval, SpecialFloat};
+pub use operator::{AssignOp, OperatorSet};
+pub use span::{Span, Spanned};
+pub use template::Segment;
+pub use trivia::Trivia;
+pub use width::{char_width, display_column_to_byte, tab_advance};
+pub use words::Words;
+
 /// Base type for line and column numbers.
 pub type Counter = u32;
 
+/// Returns `true` when `ch` terminates a line (e.g. `\n`, `\r`, form feed or the Unicode
+/// line and paragraph separators).
+pub fn is_line_terminator(ch: char) -> bool {
+    matches!(ch, '\n' | '\r' | '\u{000b}' | '\u{000c}' | '\u{0085}' | '\u{2028}' | '\u{2029}')
+}
+
+/// Returns `true` for the bytes of the ASCII line terminators.
+fn is_ascii_line_terminator(b: u8) -> bool {
+    matches!(b, b'\n' | b'\r' | 0x0b | 0x0c)
+}
+
+/// Returns the length in bytes of the line terminator at the start of `s` or 0 if `s` does not
+/// start with a line terminator. `\r\n` is treated as a single terminator.
+pub(crate) fn line_terminator_len(s: &str) -> usize {
+    if s.starts_with("\r\n") {
+        return 2;
+    }
+    match s.chars().next() {
+        Some(ch) if is_line_terminator(ch) => ch.len_utf8(),
+        _ => 0,
+    }
+}
+
 /// Position in a text by line and column numbers.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Position {
@@ -26,6 +99,12 @@ pub struct Position {
     pub column: Counter,
 }
 
+impl Default for Position {
+    fn default() -> Self {
+        Position::new()
+    }
+}
+
 impl Position {
     /// Creates an invalid `Position` (e.g. line = 0, column = 0).
     pub fn new() -> Self {
@@ -50,20 +129,58 @@ impl Position {
     }
 }
 
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Text<'a> {
+    source: &'a str,
     iter: Chars<'a>,
     position: Position, // position of NEXT character to be returned by `next()`
     next: Option<char>,
     marker: Option<Chars<'a>>,
     last_was_cr: bool,
+    tab_width: Counter,
+    source_name: Option<&'a str>,
 }
 
 impl<'a> Text<'a> {
     /// Creates a new `Text` that wraps the given source text.
     pub fn with_str(text: &'a str) -> Self {
-        Text { iter: text.chars(), position: Position::with(1,1),
-            next: None, marker: None, last_was_cr: false }
+        Text { source: text, iter: text.chars(), position: Position::with(1,1),
+            next: None, marker: None, last_was_cr: false, tab_width: 4, source_name: None }
+    }
+
+    /// Returns the number of columns between two tab stops used for measuring indentation and
+    /// display widths (defaults to 4).
+    pub fn tab_width(&self) -> Counter {
+        self.tab_width
+    }
+
+    /// Sets the number of columns between two tab stops.
+    pub fn set_tab_width(&mut self, tab_width: Counter) {
+        self.tab_width = tab_width;
+    }
+
+    /// Returns the name of the source (e.g. a file name) if one has been set.
+    pub fn source_name(&self) -> Option<&'a str> {
+        self.source_name
+    }
+
+    /// Sets the name of the source reported by `source_name()`, e.g. the file name of a `#line`
+    /// directive.
+    pub fn set_source_name(&mut self, name: &'a str) {
+        self.source_name = Some(name);
+    }
+
+    /// Renumbers the text like a `#line` directive: the NEXT character is considered to be at
+    /// column 1 of line `new_line` and the following positions are counted from there. Call this
+    /// at the start of the line following the directive.
+    pub fn apply_line_directive(&mut self, new_line: Counter) {
+        self.position = Position::with(new_line, 1);
     }
 
     /// Returns the position of the NEXT character that will be returned by `next()`
@@ -71,13 +188,34 @@ impl<'a> Text<'a> {
         &self.position
     }
 
+    /// Returns the byte offset of the NEXT character within the source text.
+    pub fn offset(&self) -> usize {
+        self.source.len() - self.iter.as_str().len() - self.next.map_or(0, |ch| ch.len_utf8())
+    }
+
+    /// Returns the not yet consumed rest of the source text.
+    pub fn remaining(&self) -> &'a str {
+        &self.source[self.offset()..]
+    }
+
+    /// Returns the bytes of the text not yet read.
+    pub fn as_bytes_remaining(&self) -> &'a [u8] {
+        self.remaining().as_bytes()
+    }
+
+    /// Returns the character starting at `byte_offset` within the source text or `None` if the
+    /// offset is out of range or not at a character boundary.
+    pub fn char_at_byte(&self, byte_offset: usize) -> Option<char> {
+        self.source.get(byte_offset..)?.chars().next()
+    }
+
     /// Returns the next char or None if EOF, but does not consume the character.
     /// The position will not be updated.
     pub fn peek_next(&mut self) -> Option<char> {
         if self.next.is_none() {
             self.next = self.iter.next();
         }
-        self.next.clone()
+        self.next
     }
 
     /// Sets the marker at the current reading position.
@@ -111,6 +249,59 @@ impl<'a> Text<'a> {
         s.get(0.. len).unwrap()
     }
 
+    /// Consumes the next `len` bytes of the source text and returns them as slice.
+    /// `len` must end at a character boundary.
+    pub(crate) fn consume_bytes(&mut self, len: usize) -> &'a str {
+        let start = self.offset();
+        let end = start + len;
+        if self.source.as_bytes()[start..end].iter().all(|b| b.is_ascii() && !is_ascii_line_terminator(*b)) {
+            self.advance_ascii_run(len);
+        }
+        while self.offset() < end {
+            let _ = self.next();
+        }
+        &self.source[start..end]
+    }
+
+    /// Consumes the next `len` bytes at once instead of character by character. The bytes must
+    /// be ASCII characters that do not terminate a line, since the position is only advanced by
+    /// `len` columns.
+    pub(crate) fn advance_ascii_run(&mut self, len: usize) {
+        let start = self.offset();
+        let run = &self.source.as_bytes()[start..start + len];
+        debug_assert!(run.is_ascii(), "bulk advance over non-ASCII text at {}", self.position);
+        debug_assert!(!run.iter().any(|b| is_ascii_line_terminator(*b)),
+                      "bulk advance across a line terminator at {}", self.position);
+        if len == 0 {
+            return;
+        }
+        self.iter = self.source[start + len..].chars();
+        self.next = None;
+        self.position.column += len as Counter;
+        self.last_was_cr = false;
+    }
+
+    /// Consumes the next char if it is `expected` or returns `ScanError::ExpectedChar` otherwise.
+    pub(crate) fn expect_char(&mut self, expected: char) -> Result<(), ScanError> {
+        match self.peek_next() {
+            Some(ch) if ch == expected => { let _ = self.next(); Ok(()) },
+            found => Err(ScanError::ExpectedChar{ expected, found, at: self.position.clone() }),
+        }
+    }
+
+    /// Returns the position the reading position would have after consuming the next `len`
+    /// bytes.
+    pub(crate) fn position_after(&self, len: usize) -> Position {
+        let mut probe = self.clone();
+        probe.consume_bytes(len);
+        probe.position
+    }
+
+    /// Wraps `value` into a `Spanned` ranging from `start` up to the current position.
+    pub(crate) fn spanned<T>(&self, start: Position, value: T) -> Spanned<T> {
+        Spanned::with(value, Span::with(start, self.position.clone()))
+    }
+
     fn advance_position(&mut self, ch: &char) {
         match ch {
             '\r' => {
@@ -120,8 +311,8 @@ impl<'a> Text<'a> {
             '\n' => {
                 if !self.last_was_cr {
                     self.position.advance_line();
-                    self.last_was_cr = false;
                 }
+                self.last_was_cr = false;
             },
             '\u{000b}' => {
                 self.position.line += 1;
@@ -166,6 +357,19 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn apply_line_directive() {
+        let mut text = Text::with_str("#line 42 \"orig.c\"\nint x;\ny");
+        text.take_line();
+        text.apply_line_directive(42);
+        text.set_source_name("orig.c");
+
+        assert_eq!(text.position(), &Position::with(42, 1));
+        text.take_line();
+        assert_eq!(text.position(), &Position::with(43, 1));
+        assert_eq!(text.source_name(), Some("orig.c"));
+    }
+
     #[test]
     fn test_marker() {
         let mut text = Text::with_str( " some_value_ 1" );
@@ -184,6 +388,37 @@ mod tests {
     }
 
 
+    #[test]
+    fn advance_ascii_run() {
+        let mut text = Text::with_str("abcdef\nx");
+
+        assert_eq!(text.peek_next(), Some('a'));
+        text.advance_ascii_run(4);
+        assert_eq!(text.position(), &Position::with(1, 5));
+        assert_eq!(text.next(), Some('e'));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "bulk advance across a line terminator")]
+    fn advance_ascii_run_across_newline() {
+        let mut text = Text::with_str("ab\ncd");
+        text.advance_ascii_run(4);
+    }
+
+    #[test]
+    fn char_at_byte() {
+        let mut text = Text::with_str("héllo");
+
+        let _ = text.next();
+        let _ = text.next();
+        assert_eq!(text.char_at_byte(0), Some('h'));
+        assert_eq!(text.char_at_byte(1), Some('é'));
+        assert_eq!(text.char_at_byte(2), None);
+        assert_eq!(text.char_at_byte(5), Some('o'));
+        assert_eq!(text.char_at_byte(6), None);
+    }
+
     #[test]
     fn text_peek() {
         let src = "This is my text\nwith three lines.\n";
@@ -0,0 +1,456 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{is_line_terminator, line_terminator_len, tab_advance, Counter, ScanError, Span, Spanned, Text};
+
+/// Kind of a line terminator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// `\r`
+    Cr,
+    /// vertical tab `\u{000b}`
+    VerticalTab,
+    /// form feed `\u{000c}`
+    FormFeed,
+    /// next line `\u{0085}`
+    NextLine,
+    /// line separator `\u{2028}`
+    LineSeparator,
+    /// paragraph separator `\u{2029}`
+    ParagraphSeparator,
+}
+
+impl LineEnding {
+    /// Returns the line ending at the start of `s` or `None` if `s` does not start with a line
+    /// terminator.
+    pub fn at_start(s: &str) -> Option<LineEnding> {
+        if s.starts_with("\r\n") {
+            return Some(LineEnding::CrLf);
+        }
+        match s.chars().next()? {
+            '\n' => Some(LineEnding::Lf),
+            '\r' => Some(LineEnding::Cr),
+            '\u{000b}' => Some(LineEnding::VerticalTab),
+            '\u{000c}' => Some(LineEnding::FormFeed),
+            '\u{0085}' => Some(LineEnding::NextLine),
+            '\u{2028}' => Some(LineEnding::LineSeparator),
+            '\u{2029}' => Some(LineEnding::ParagraphSeparator),
+            _ => None,
+        }
+    }
+
+    /// Returns the characters of the line ending.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+            LineEnding::VerticalTab => "\u{000b}",
+            LineEnding::FormFeed => "\u{000c}",
+            LineEnding::NextLine => "\u{0085}",
+            LineEnding::LineSeparator => "\u{2028}",
+            LineEnding::ParagraphSeparator => "\u{2029}",
+        }
+    }
+}
+
+/// Returns the content of the first line within `s`, i.e. `s` up to (excluding) the first line
+/// terminator.
+pub(crate) fn line_content(s: &str) -> &str {
+    &s[..s.find(is_line_terminator).unwrap_or(s.len())]
+}
+
+/// Returns `true` if `line` consists of whitespace only.
+pub(crate) fn is_blank(line: &str) -> bool {
+    line.chars().all(char::is_whitespace)
+}
+
+/// Returns the width of the leading spaces and tabs of `line` in columns.
+pub(crate) fn indent_width(line: &str, tab_width: Counter) -> Counter {
+    let mut width: Counter = 0;
+    for ch in line.chars() {
+        match ch {
+            ' ' => width = width.saturating_add(1),
+            '\t' => width = width.saturating_add(tab_advance(width.saturating_add(1), tab_width)),
+            _ => break,
+        }
+    }
+    width
+}
+
+impl<'a> Text<'a> {
+    /// Scans a single line terminator (with `\r\n` as one terminator).
+    /// Returns `None` without consuming anything if the next char does not terminate a line.
+    pub fn scan_line_ending(&mut self) -> Option<Spanned<LineEnding>> {
+        let ending = LineEnding::at_start(self.remaining())?;
+        let start = self.position.clone();
+        self.consume_bytes(ending.as_str().len());
+        Some(self.spanned(start, ending))
+    }
+
+    /// Consumes the rest of the current line including its terminator and returns the line's
+    /// content and the terminator as separate slices.
+    pub(crate) fn take_line(&mut self) -> (&'a str, &'a str) {
+        let rest = self.remaining();
+        let content_len = line_content(rest).len();
+        let ending_len = line_terminator_len(&rest[content_len..]);
+        let content = self.consume_bytes(content_len);
+        let ending = self.consume_bytes(ending_len);
+        (content, ending)
+    }
+
+    /// Scans the body of a heredoc, i.e. all lines up to a line that equals `label`. With
+    /// `strip_indent` set, leading tabs are removed from all lines before comparing them with
+    /// `label` and before adding them to the body (like `<<-` in shells).
+    /// The terminating line is consumed but not part of the returned body; the span covers all
+    /// consumed lines. Reaching EOF before the terminating line is an error.
+    pub fn scan_heredoc(&mut self, label: &str, strip_indent: bool) -> Result<Spanned<String>, ScanError> {
+        let start = self.position.clone();
+        let mut body = String::new();
+        loop {
+            if self.peek_next().is_none() {
+                return Err(ScanError::Unterminated{ start, at: self.position.clone() });
+            }
+            let (line, ending) = self.take_line();
+            let line = if strip_indent { line.trim_start_matches('\t') } else { line };
+            if line == label {
+                break;
+            }
+            body.push_str(line);
+            body.push_str(ending);
+        }
+        Ok(self.spanned(start, body))
+    }
+
+    /// Consumes all lines (including blank lines) that are indented by at least `min_indent`
+    /// columns (tabs are expanded to `tab_width()`) and returns them as one slice including the
+    /// line terminators. Scanning stops before the first non-blank line that is less indented.
+    /// The reading position must be at the start of a line.
+    pub fn scan_indented_block(&mut self, min_indent: Counter) -> Spanned<&'a str> {
+        let start = self.position.clone();
+        let start_offset = self.offset();
+        loop {
+            let line = line_content(self.remaining());
+            if self.remaining().is_empty()
+                || (!is_blank(line) && indent_width(line, self.tab_width) < min_indent) {
+                break;
+            }
+            self.take_line();
+        }
+        self.spanned(start, &self.source[start_offset..self.offset()])
+    }
+
+    /// Splits the following lines into sibling blocks, each starting at a line indented by exactly
+    /// `indent` columns and extending through all following blank or more indented lines.
+    /// Blank lines before a block are skipped. Scanning stops at EOF or before a non-blank line
+    /// that does not start a block. The reading position must be at the start of a line.
+    pub fn scan_sibling_blocks(&mut self, indent: Counter) -> Vec<Spanned<&'a str>> {
+        let mut blocks = Vec::new();
+        loop {
+            let checkpoint = self.clone();
+            while !self.remaining().is_empty() && is_blank(line_content(self.remaining())) {
+                self.take_line();
+            }
+            let line = line_content(self.remaining());
+            if self.remaining().is_empty() || indent_width(line, self.tab_width) != indent {
+                *self = checkpoint;
+                break;
+            }
+            let start = self.position.clone();
+            let start_offset = self.offset();
+            self.take_line();
+            if let Some(child_indent) = indent.checked_add(1) {
+                self.scan_indented_block(child_indent);
+            }
+            blocks.push(self.spanned(start, &self.source[start_offset..self.offset()]));
+        }
+        blocks
+    }
+
+    /// Reads a logical line, i.e. physical lines joined where a line ends with a backslash, and
+    /// returns its content without the backslash-newline joins and without the final line
+    /// terminator. The span includes the final line terminator.
+    /// Returns `None` at EOF.
+    pub fn read_logical_line(&mut self) -> Option<Spanned<String>> {
+        if self.remaining().is_empty() {
+            return None;
+        }
+        let start = self.position.clone();
+        let mut line = String::new();
+        loop {
+            let (content, ending) = self.take_line();
+            match content.strip_suffix('\\') {
+                Some(content) if !ending.is_empty() => line.push_str(content),
+                _ => {
+                    line.push_str(content);
+                    break;
+                },
+            }
+        }
+        Some(self.spanned(start, line))
+    }
+
+    /// Consumes all lines until the first non-blank line that is indented by at most
+    /// `base_indent` columns, which is not consumed. Returns the consumed lines including blank
+    /// lines and line terminators. The reading position must be at the start of a line.
+    pub fn scan_until_dedent_to(&mut self, base_indent: Counter) -> Spanned<&'a str> {
+        match base_indent.checked_add(1) {
+            Some(min_indent) => self.scan_indented_block(min_indent),
+            None => self.consume_lines_while(is_blank),
+        }
+    }
+
+    /// Consumes whole lines (including their line terminators) as long as `pred` holds for their
+    /// content and returns them. The first line for which `pred` fails is not consumed.
+    /// The reading position must be at the start of a line.
+    pub fn consume_lines_while(&mut self, mut pred: impl FnMut(&str) -> bool) -> Spanned<&'a str> {
+        let start = self.position.clone();
+        let start_offset = self.offset();
+        while !self.remaining().is_empty() && pred(line_content(self.remaining())) {
+            self.take_line();
+        }
+        self.spanned(start, &self.source[start_offset..self.offset()])
+    }
+
+    /// Skips blank lines and scans the following paragraph, i.e. all lines up to a blank line or
+    /// EOF. Returns the paragraph without its final line terminator, which is consumed but not
+    /// covered by the span. Returns `None` if only blank lines follow.
+    /// The reading position must be at the start of a line.
+    pub fn scan_paragraph(&mut self) -> Option<Spanned<&'a str>> {
+        while !self.remaining().is_empty() && is_blank(line_content(self.remaining())) {
+            self.take_line();
+        }
+        if self.remaining().is_empty() {
+            return None;
+        }
+        let start = self.position.clone();
+        let start_offset = self.offset();
+        let mut end = (start_offset, start.clone());
+        while !self.remaining().is_empty() && !is_blank(line_content(self.remaining())) {
+            self.consume_bytes(line_content(self.remaining()).len());
+            end = (self.offset(), self.position.clone());
+            self.consume_bytes(line_terminator_len(self.remaining()));
+        }
+        Some(Spanned::with(&self.source[start_offset..end.0], Span::with(start, end.1)))
+    }
+
+    /// Consumes whole lines until a line starts with `prefix` or EOF is reached. The line
+    /// starting with `prefix` is not consumed. Returns the consumed lines including their line
+    /// terminators. The reading position is considered as start of a line.
+    pub fn scan_until_line_prefix(&mut self, prefix: &str) -> Spanned<&'a str> {
+        let start = self.position.clone();
+        let start_offset = self.offset();
+        while !self.remaining().is_empty() && !self.remaining().starts_with(prefix) {
+            self.take_line();
+        }
+        self.spanned(start, &self.source[start_offset..self.offset()])
+    }
+
+    /// Consumes whole lines up to and including a MIME boundary line `--boundary` or a closing
+    /// boundary line `--boundary--` (trailing whitespace is ignored). Returns the body before the
+    /// boundary line without the line terminator preceding it and `true` for a closing boundary.
+    /// The span covers the body. The reading position must be at the start of a line.
+    /// Returns `ScanError::Unterminated` (after consuming the rest of the text) if no boundary
+    /// line follows.
+    pub fn scan_until_boundary(&mut self, boundary: &str) -> Result<(Spanned<&'a str>, bool), ScanError> {
+        let start = self.position.clone();
+        let body_start = self.offset();
+        let mut body_end = (body_start, start.clone());
+        while !self.remaining().is_empty() {
+            let line = line_content(self.remaining()).trim_end();
+            if let Some(rest) = line.strip_prefix("--").and_then(|l| l.strip_prefix(boundary)) {
+                if rest.is_empty() || rest == "--" {
+                    self.take_line();
+                    let body = &self.source[body_start..body_end.0];
+                    return Ok((Spanned::with(body, Span::with(start, body_end.1)), !rest.is_empty()));
+                }
+            }
+            let content = line_content(self.remaining()).len();
+            self.consume_bytes(content);
+            body_end = (self.offset(), self.position.clone());
+            let ending = line_terminator_len(self.remaining());
+            self.consume_bytes(ending);
+        }
+        Err(ScanError::Unterminated{ start, at: self.position.clone() })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_line_ending() {
+        let mut text = Text::with_str("\r\n\rx");
+
+        let ending = text.scan_line_ending().unwrap();
+        assert_eq!(ending.value, LineEnding::CrLf);
+        assert_eq!(ending.span, Span::with(Position::with(1, 1), Position::with(2, 1)));
+        assert_eq!(text.scan_line_ending().unwrap().value, LineEnding::Cr);
+        assert_eq!(text.scan_line_ending(), None);
+    }
+
+    #[test]
+    fn scan_heredoc() {
+        let mut text = Text::with_str("line 1\n  line 2\nEOF\nrest");
+
+        let doc = text.scan_heredoc("EOF", false).unwrap();
+        assert_eq!(doc.value, "line 1\n  line 2\n");
+        assert_eq!(doc.span, Span::with(Position::with(1, 1), Position::with(4, 1)));
+        assert_eq!(text.next(), Some('r'));
+    }
+
+    #[test]
+    fn scan_heredoc_strip_indent() {
+        let mut text = Text::with_str("\tline 1\n\t\tline 2\n\tEOF");
+
+        let doc = text.scan_heredoc("EOF", true).unwrap();
+        assert_eq!(doc.value, "line 1\nline 2\n");
+        assert_eq!(text.position(), &Position::with(3, 5));
+        assert_eq!(text.next(), None);
+    }
+
+    #[test]
+    fn scan_heredoc_unterminated() {
+        let mut text = Text::with_str("line 1\nEO");
+
+        assert_eq!(text.scan_heredoc("EOF", false),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(2, 3) }));
+    }
+
+    #[test]
+    fn scan_indented_block() {
+        let mut text = Text::with_str("    a\n\tb\n\n  c\n");
+
+        let block = text.scan_indented_block(4);
+        assert_eq!(block.value, "    a\n\tb\n\n");
+        assert_eq!(block.span, Span::with(Position::with(1, 1), Position::with(4, 1)));
+        assert_eq!(text.next(), Some(' '));
+    }
+
+    #[test]
+    fn scan_until_dedent_to() {
+        let mut text = Text::with_str("   a\n      b\n\n    c\n  d\n");
+
+        let block = text.scan_until_dedent_to(2);
+        assert_eq!(block.value, "   a\n      b\n\n    c\n");
+        assert_eq!(block.span, Span::with(Position::with(1, 1), Position::with(5, 1)));
+        assert_eq!(text.scan_until_dedent_to(2).value, "");
+        assert_eq!(text.remaining(), "  d\n");
+        assert_eq!(text.scan_until_dedent_to(Counter::MAX), Spanned::with("", Span::empty_at(Position::with(5, 1))));
+        assert_eq!(text.remaining(), "  d\n");
+
+        let mut text = Text::with_str("\n\tx\n");
+        text.set_tab_width(Counter::MAX);
+        assert_eq!(text.scan_until_dedent_to(Counter::MAX).value, "\n");
+        assert_eq!(text.remaining(), "\tx\n");
+    }
+
+    #[test]
+    fn read_logical_line() {
+        let mut text = Text::with_str("a\\\nb\nc");
+
+        let line = text.read_logical_line().unwrap();
+        assert_eq!(line.value, "ab");
+        assert_eq!(line.span, Span::with(Position::with(1, 1), Position::with(3, 1)));
+        assert_eq!(text.read_logical_line().unwrap().value, "c");
+        assert_eq!(text.read_logical_line(), None);
+    }
+
+    #[test]
+    fn scan_until_line_prefix() {
+        let mut text = Text::with_str(" a\n-b\n @@\n@@ -1 +1 @@\n");
+
+        let block = text.scan_until_line_prefix("@@");
+        assert_eq!(block.value, " a\n-b\n @@\n");
+        assert_eq!(block.span, Span::with(Position::with(1, 1), Position::with(4, 1)));
+        assert_eq!(text.scan_until_line_prefix("@@").value, "");
+        assert_eq!(text.next(), Some('@'));
+    }
+
+    #[test]
+    fn scan_until_boundary() {
+        let mut text = Text::with_str("part one\r\nline 2\r\n--xyz\r\n--xyz--\r\nepilogue");
+
+        let (body, closing) = text.scan_until_boundary("xyz").unwrap();
+        assert_eq!(body.value, "part one\r\nline 2");
+        assert_eq!(body.span, Span::with(Position::with(1, 1), Position::with(2, 7)));
+        assert!(!closing);
+        assert_eq!(text.position(), &Position::with(4, 1));
+
+        let (body, closing) = text.scan_until_boundary("xyz").unwrap();
+        assert_eq!(body.value, "");
+        assert!(closing);
+        assert_eq!(text.next(), Some('e'));
+
+        let mut text = Text::with_str("no\n--xyzz\n");
+        assert_eq!(text.scan_until_boundary("xyz"),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(3, 1) }));
+    }
+
+    #[test]
+    fn scan_sibling_blocks() {
+        let mut text = Text::with_str("- a\n  child a\n\n- b\n  child b\nend\n");
+
+        let blocks = text.scan_sibling_blocks(0);
+        assert_eq!(blocks[0].value, "- a\n  child a\n\n");
+        assert_eq!(blocks[0].span, Span::with(Position::with(1, 1), Position::with(4, 1)));
+        assert_eq!(blocks[1].value, "- b\n  child b\n");
+        assert_eq!(blocks[2].value, "end\n");
+        assert_eq!(blocks.len(), 3);
+
+        let mut text = Text::with_str("  - a\n    child\n  - b\n\nend");
+        let blocks: Vec<_> = text.scan_sibling_blocks(2).into_iter().map(|block| block.value).collect();
+        assert_eq!(blocks, vec!["  - a\n    child\n", "  - b\n\n"]);
+        assert_eq!(text.next(), Some('e'));
+    }
+
+    #[test]
+    fn scan_sibling_blocks_max_indent() {
+        let mut text = Text::with_str("\tx\n\ty\n");
+        text.set_tab_width(Counter::MAX);
+
+        let blocks: Vec<_> = text.scan_sibling_blocks(Counter::MAX).into_iter().map(|block| block.value).collect();
+        assert_eq!(blocks, vec!["\tx\n", "\ty\n"]);
+    }
+
+    #[test]
+    fn consume_lines_while() {
+        let mut text = Text::with_str("> one\n> two\r\nthree\n> four");
+
+        let quote = text.consume_lines_while(|line| line.starts_with("> "));
+        assert_eq!(quote.value, "> one\n> two\r\n");
+        assert_eq!(quote.span, Span::with(Position::with(1, 1), Position::with(3, 1)));
+        assert_eq!(text.next(), Some('t'));
+    }
+
+    #[test]
+    fn scan_paragraph() {
+        let mut text = Text::with_str("a\nb\n\n \nc");
+
+        let paragraph = text.scan_paragraph().unwrap();
+        assert_eq!(paragraph.value, "a\nb");
+        assert_eq!(paragraph.span, Span::with(Position::with(1, 1), Position::with(2, 2)));
+        let paragraph = text.scan_paragraph().unwrap();
+        assert_eq!(paragraph.value, "c");
+        assert_eq!(paragraph.span, Span::with(Position::with(5, 1), Position::with(5, 2)));
+        assert_eq!(text.scan_paragraph(), None);
+    }
+}
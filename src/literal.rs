@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{ScanError, Spanned, Text};
+
+impl<'a> Text<'a> {
+    /// Scans a hex color of the form `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA` and returns its
+    /// RGBA components. Shorthand digits are expanded (`#fa0` is `#ffaa00`) and a missing alpha
+    /// component defaults to 255.
+    /// Returns `None` without consuming anything when the next char is not `#` or when it is not
+    /// followed by a valid number of hex digits.
+    pub fn scan_hex_color(&mut self) -> Option<Spanned<(u8, u8, u8, u8)>> {
+        let rest = self.remaining().strip_prefix('#')?;
+        let digits = rest.bytes().take_while(u8::is_ascii_hexdigit).count();
+        let hex = &rest[..digits];
+        let (r, g, b, a) = match digits {
+            3 | 4 => {
+                let nibble = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).unwrap() * 0x11;
+                let a = if digits == 4 { nibble(3) } else { 255 };
+                (nibble(0), nibble(1), nibble(2), a)
+            },
+            6 | 8 => {
+                let byte = |i: usize| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+                let a = if digits == 8 { byte(3) } else { 255 };
+                (byte(0), byte(1), byte(2), a)
+            },
+            _ => return None,
+        };
+
+        let start = self.position.clone();
+        self.consume_bytes(1 + digits);
+        Some(self.spanned(start, (r, g, b, a)))
+    }
+
+    /// Scans a code point in `U+XXXX` notation with one to six hex digits and returns the
+    /// corresponding char. Returns `None` without consuming anything if the text does not
+    /// continue with `U+`, `ScanError::ExpectedDigits` (after consuming `U+`) if no hex digit
+    /// follows and `ScanError::InvalidCodePoint` (after consuming the digits) for surrogates and
+    /// values beyond U+10FFFF.
+    pub fn scan_unicode_codepoint(&mut self) -> Option<Result<Spanned<char>, ScanError>> {
+        let digits = self.remaining().strip_prefix("U+")?;
+        let start = self.position.clone();
+        self.consume_bytes(2);
+        let len = digits.bytes().take(6).take_while(u8::is_ascii_hexdigit).count();
+        if len == 0 {
+            return Some(Err(ScanError::ExpectedDigits{ at: self.position.clone(), needed: 1 }));
+        }
+        let value = u32::from_str_radix(self.consume_bytes(len), 16).unwrap();
+        Some(match char::from_u32(value) {
+            Some(ch) => Ok(self.spanned(start, ch)),
+            None => Err(ScanError::InvalidCodePoint{ value, at: start }),
+        })
+    }
+
+    /// Scans a run of base64 chars (`A-Z`, `a-z`, `0-9`, `+`, `/`) followed by up to two `=` of
+    /// padding. Returns `None` without consuming anything if the run (including the padding) is
+    /// shorter than four chars. The run is not decoded.
+    pub fn scan_base64(&mut self) -> Option<Spanned<&'a str>> {
+        let rest = self.remaining();
+        let chars = rest.bytes().take_while(|&b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/').count();
+        let padding = rest[chars..].bytes().take(2).take_while(|&b| b == b'=').count();
+        if chars + padding < 4 {
+            return None;
+        }
+        let start = self.position.clone();
+        let run = self.consume_bytes(chars + padding);
+        Some(self.spanned(start, run))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_hex_color() {
+        let mut text = Text::with_str("#fff #aabbcc;#aabbccdd");
+
+        let color = text.scan_hex_color().unwrap();
+        assert_eq!(color.value, (255, 255, 255, 255));
+        assert_eq!(color.span, Span::with(Position::with(1, 1), Position::with(1, 5)));
+
+        let _ = text.next();
+        assert_eq!(text.scan_hex_color().unwrap().value, (0xaa, 0xbb, 0xcc, 255));
+        assert_eq!(text.next(), Some(';'));
+        assert_eq!(text.scan_hex_color().unwrap().value, (0xaa, 0xbb, 0xcc, 0xdd));
+        assert_eq!(text.next(), None);
+    }
+
+    #[test]
+    fn scan_hex_color_invalid() {
+        let mut text = Text::with_str("#12 ");
+
+        assert_eq!(text.scan_hex_color(), None);
+        assert_eq!(text.position(), &Position::with(1, 1));
+        assert_eq!(text.next(), Some('#'));
+        assert_eq!(text.scan_hex_color(), None);
+    }
+
+    #[test]
+    fn scan_base64() {
+        let mut text = Text::with_str("aGk/+Q== rest");
+
+        let run = text.scan_base64().unwrap();
+        assert_eq!(run.value, "aGk/+Q==");
+        assert_eq!(run.span, Span::with(Position::with(1, 1), Position::with(1, 9)));
+        assert_eq!(text.next(), Some(' '));
+    }
+
+    #[test]
+    fn scan_base64_too_short() {
+        let mut text = Text::with_str("ab= ");
+
+        assert_eq!(text.scan_base64(), None);
+        assert_eq!(text.next(), Some('a'));
+    }
+
+    #[test]
+    fn scan_unicode_codepoint() {
+        let mut text = Text::with_str("U+0041 U+1F600 u+41");
+
+        let ch = text.scan_unicode_codepoint().unwrap().unwrap();
+        assert_eq!(ch.value, 'A');
+        assert_eq!(ch.span, Span::with(Position::with(1, 1), Position::with(1, 7)));
+        let _ = text.next();
+        assert_eq!(text.scan_unicode_codepoint().unwrap().unwrap().value, '😀');
+        let _ = text.next();
+        assert_eq!(text.scan_unicode_codepoint(), None);
+        assert_eq!(text.next(), Some('u'));
+    }
+
+    #[test]
+    fn scan_unicode_codepoint_invalid() {
+        let mut text = Text::with_str("U+110000");
+        assert_eq!(text.scan_unicode_codepoint(),
+                   Some(Err(ScanError::InvalidCodePoint{ value: 0x110000, at: Position::with(1, 1) })));
+
+        let mut text = Text::with_str("U+D800");
+        assert_eq!(text.scan_unicode_codepoint(),
+                   Some(Err(ScanError::InvalidCodePoint{ value: 0xd800, at: Position::with(1, 1) })));
+
+        let mut text = Text::with_str("U+x");
+        assert_eq!(text.scan_unicode_codepoint(), Some(Err(ScanError::ExpectedDigits{ at: Position::with(1, 3), needed: 1 })));
+        assert_eq!(text.next(), Some('x'));
+    }
+}
@@ -0,0 +1,401 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ident::ident_len;
+use crate::lines::{is_blank, line_content};
+use crate::{is_line_terminator, ScanError, Spanned, Text};
+
+/// Fenced code block scanned by `Text::scan_fenced_block()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FencedBlock<'a> {
+    /// the fence char, either `` ` `` or `~`
+    pub fence: char,
+    /// the number of fence chars of the opening fence
+    pub len: usize,
+    /// the trimmed info string following the opening fence
+    pub info: &'a str,
+    /// the lines between the fences including their line terminators
+    pub body: &'a str,
+}
+
+/// Named HTML entities recognized by `Text::scan_html_entity()`.
+const ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'), ("lt", '<'), ("gt", '>'), ("quot", '"'), ("apos", '\''), ("nbsp", '\u{a0}'),
+    ("copy", '©'), ("reg", '®'), ("trade", '™'), ("deg", '°'), ("plusmn", '±'), ("times", '×'),
+    ("divide", '÷'), ("middot", '·'), ("para", '¶'), ("sect", '§'), ("cent", '¢'), ("pound", '£'),
+    ("yen", '¥'), ("euro", '€'), ("laquo", '«'), ("raquo", '»'), ("ndash", '–'), ("mdash", '—'),
+    ("hellip", '…'), ("lsquo", '‘'), ("rsquo", '’'), ("ldquo", '“'), ("rdquo", '”'), ("bull", '•'),
+];
+
+/// Returns the fence char and the length of the fence at the start of `line` (after up to three
+/// spaces of indentation) and the rest of the line after the fence.
+fn fence(line: &str) -> Option<(char, usize, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let fence = trimmed.chars().next().filter(|&ch| ch == '`' || ch == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(fence).len();
+    if len < 3 {
+        return None;
+    }
+    Some((fence, len, &trimmed[len..]))
+}
+
+impl<'a> Text<'a> {
+    /// Scans an XML-like tag name that starts with an alphabetic char, `_` or `:` and continues
+    /// with alphanumeric chars, `-`, `_`, `.` or `:`. The surrounding `<`, `</` or `>` are left to
+    /// the caller.
+    /// Returns `None` without consuming anything if the next char cannot start a name.
+    pub fn scan_tag_name(&mut self) -> Option<Spanned<&'a str>> {
+        let len = ident_len(self.remaining(), &[':'], &['-', '.', ':']);
+        if len == 0 {
+            return None;
+        }
+        let start = self.position.clone();
+        let name = self.consume_bytes(len);
+        Some(self.spanned(start, name))
+    }
+
+    /// Scans a Markdown fenced code block: an opening fence of at least three backticks or tildes
+    /// (indented by at most three spaces) followed by an optional info string, the body lines and
+    /// a closing fence of the same char that is at least as long as the opening fence.
+    /// The reading position must be at the start of a line. Returns `None` without consuming
+    /// anything if the current line is no opening fence and `ScanError::Unterminated` if the
+    /// closing fence is missing.
+    pub fn scan_fenced_block(&mut self) -> Option<Result<Spanned<FencedBlock<'a>>, ScanError>> {
+        let (fence_char, len, info) = fence(line_content(self.remaining()))?;
+        let info = info.trim();
+        if fence_char == '`' && info.contains('`') {
+            return None;
+        }
+
+        let start = self.position.clone();
+        self.take_line();
+        let body_start = self.offset();
+        loop {
+            if self.remaining().is_empty() {
+                return Some(Err(ScanError::Unterminated{ start, at: self.position.clone() }));
+            }
+            let body_end = self.offset();
+            let closing = fence(line_content(self.remaining()))
+                .is_some_and(|(ch, n, rest)| ch == fence_char && n >= len && is_blank(rest));
+            self.take_line();
+            if closing {
+                let body = &self.source[body_start..body_end];
+                return Some(Ok(self.spanned(start, FencedBlock{ fence: fence_char, len, info, body })));
+            }
+        }
+    }
+
+    /// Scans a Markdown code span delimited by a run of backticks and closed by a run of the same
+    /// length, e.g. ``` `` a`b `` ```. Like in CommonMark one leading and one trailing space are
+    /// removed from the returned content if both are present and the content is not only spaces.
+    /// Returns `None` without consuming anything if no backtick run starts at the reading
+    /// position or if it is not closed.
+    pub fn scan_code_span(&mut self) -> Option<Spanned<&'a str>> {
+        let rest = self.remaining();
+        let backticks = |s: &str| s.bytes().take_while(|&b| b == b'`').count();
+        let len = backticks(rest);
+        if len == 0 {
+            return None;
+        }
+        let mut index = len;
+        let close = loop {
+            let found = index + rest[index..].find('`')?;
+            let run = backticks(&rest[found..]);
+            if run == len {
+                break found;
+            }
+            index = found + run;
+        };
+
+        let mut content = &rest[len..close];
+        if content.len() >= 2 && content.starts_with(' ') && content.ends_with(' ')
+            && !content.bytes().all(|b| b == b' ') {
+            content = &content[1..content.len() - 1];
+        }
+        let start = self.position.clone();
+        self.consume_bytes(close + len);
+        Some(self.spanned(start, content))
+    }
+
+    /// Scans a Markdown inline link `[text](url)` and returns its text and URL with backslash
+    /// escapes resolved. The text may contain balanced or escaped brackets; the URL may contain
+    /// balanced or escaped parentheses or be enclosed in `<` and `>`.
+    /// Returns `None` without consuming anything if the next char is not `[` and an error if the
+    /// link is malformed after that.
+    pub fn scan_md_link(&mut self) -> Option<Result<Spanned<(String, String)>, ScanError>> {
+        if self.peek_next() != Some('[') {
+            return None;
+        }
+        Some(self.scan_md_link_committed())
+    }
+
+    /// Scans the rest of a Markdown inline link after `scan_md_link()` has checked the `[`.
+    fn scan_md_link_committed(&mut self) -> Result<Spanned<(String, String)>, ScanError> {
+        let start = self.position.clone();
+        let _ = self.next();
+        let mut label = String::new();
+        let mut depth = 0usize;
+        loop {
+            match self.next() {
+                Some(']') if depth == 0 => break,
+                Some('\\') if self.peek_next().is_some_and(|ch| ch.is_ascii_punctuation()) =>
+                    label.extend(self.next()),
+                Some(ch) => {
+                    match ch {
+                        '[' => depth += 1,
+                        ']' => depth -= 1,
+                        _ => {},
+                    }
+                    label.push(ch);
+                },
+                None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+            }
+        }
+
+        self.expect_char('(')?;
+        self.consume_while_on_line(char::is_whitespace);
+        let mut url = String::new();
+        if self.peek_next() == Some('<') {
+            let open_at = self.position.clone();
+            let _ = self.next();
+            loop {
+                match self.peek_next() {
+                    Some('>') => break,
+                    Some(ch) if !is_line_terminator(ch) => {
+                        let _ = self.next();
+                        url.push(ch);
+                    },
+                    _ => return Err(ScanError::Unterminated{ start: open_at, at: self.position.clone() }),
+                }
+            }
+            let _ = self.next();
+        }
+        else {
+            let mut depth = 0usize;
+            while let Some(ch) = self.peek_next() {
+                if ch.is_whitespace() || (ch == ')' && depth == 0) {
+                    break;
+                }
+                let _ = self.next();
+                match ch {
+                    '\\' if self.peek_next().is_some_and(|ch| ch.is_ascii_punctuation()) => url.extend(self.next()),
+                    '(' => { depth += 1; url.push(ch); },
+                    ')' => { depth -= 1; url.push(ch); },
+                    _ => url.push(ch),
+                }
+            }
+        }
+        self.consume_while_on_line(char::is_whitespace);
+        self.expect_char(')')?;
+        Ok(self.spanned(start, (label, url)))
+    }
+
+    /// Scans an HTML character reference: a named entity like `&amp;` (from a table of common
+    /// names) or a numeric reference like `&#65;` or `&#x41;`, and returns the decoded text.
+    /// Returns `None` without consuming anything if the next char is not `&` and an error for an
+    /// unknown entity, an invalid code point or a missing `;`.
+    pub fn scan_html_entity(&mut self) -> Option<Result<Spanned<String>, ScanError>> {
+        if self.peek_next() != Some('&') {
+            return None;
+        }
+        Some(self.scan_html_entity_committed())
+    }
+
+    /// Scans the rest of a character reference after `scan_html_entity()` has checked the `&`.
+    fn scan_html_entity_committed(&mut self) -> Result<Spanned<String>, ScanError> {
+        let start = self.position.clone();
+        let _ = self.next();
+        let decoded = if self.peek_next() == Some('#') {
+            let _ = self.next();
+            let radix = match self.peek_next() {
+                Some('x' | 'X') => { let _ = self.next(); 16 },
+                _ => 10,
+            };
+            let rest = self.remaining();
+            let len = rest.find(|ch: char| !ch.is_digit(radix)).unwrap_or(rest.len());
+            if len == 0 {
+                return Err(ScanError::ExpectedDigits{ at: self.position.clone(), needed: 1 });
+            }
+            let value = u32::from_str_radix(&rest[..len], radix)
+                .map_err(|_| ScanError::Overflow{ at: self.position.clone() })?;
+            self.consume_bytes(len);
+            char::from_u32(value).ok_or(ScanError::InvalidCodePoint{ value, at: start.clone() })?
+        }
+        else {
+            let name = self.remaining();
+            let name = &name[..name.find(|ch: char| !ch.is_ascii_alphanumeric()).unwrap_or(name.len())];
+            let &(_, decoded) = ENTITIES.iter().find(|(entity, _)| *entity == name)
+                .ok_or(ScanError::UnknownEntity{ at: start.clone() })?;
+            self.consume_bytes(name.len());
+            decoded
+        };
+        self.expect_char(';')?;
+        Ok(self.spanned(start, decoded.to_string()))
+    }
+
+    /// Scans the body of an HTML comment whose `<!--` has already been consumed up to the
+    /// closing `-->`, which is consumed as well. Returns the body; the span covers the body only.
+    /// Returns `ScanError::Unterminated` (after consuming the rest of the text) if there is no
+    /// `-->`.
+    pub fn scan_html_comment_body(&mut self) -> Result<Spanned<&'a str>, ScanError> {
+        let start = self.position.clone();
+        let rest = self.remaining();
+        let Some(len) = rest.find("-->") else {
+            self.consume_bytes(rest.len());
+            return Err(ScanError::Unterminated{ start, at: self.position.clone() });
+        };
+        let body = self.consume_bytes(len);
+        let body = self.spanned(start, body);
+        self.consume_bytes(3);
+        Ok(body)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_tag_name() {
+        let mut text = Text::with_str("div>");
+        let name = text.scan_tag_name().unwrap();
+        assert_eq!(name.value, "div");
+        assert_eq!(name.span, Span::with(Position::with(1, 1), Position::with(1, 4)));
+        assert_eq!(text.next(), Some('>'));
+
+        let mut text = Text::with_str("xml:lang=\"en\"");
+        assert_eq!(text.scan_tag_name().unwrap().value, "xml:lang");
+
+        let mut text = Text::with_str("1div");
+        assert_eq!(text.scan_tag_name(), None);
+        assert_eq!(text.next(), Some('1'));
+    }
+
+    #[test]
+    fn scan_fenced_block() {
+        let mut text = Text::with_str("```rust\nfn main() {}\n``\n````\nafter");
+
+        let block = text.scan_fenced_block().unwrap().unwrap();
+        assert_eq!(block.value, FencedBlock{ fence: '`', len: 3, info: "rust", body: "fn main() {}\n``\n" });
+        assert_eq!(block.span, Span::with(Position::with(1, 1), Position::with(5, 1)));
+        assert_eq!(text.scan_fenced_block(), None);
+        assert_eq!(text.next(), Some('a'));
+    }
+
+    #[test]
+    fn scan_fenced_block_unterminated() {
+        let mut text = Text::with_str("~~~\ncode\n```\n");
+
+        assert_eq!(text.scan_fenced_block(),
+                   Some(Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(4, 1) })));
+    }
+
+    #[test]
+    fn scan_code_span() {
+        let mut text = Text::with_str("`code` ``` a``b ```x");
+
+        let span = text.scan_code_span().unwrap();
+        assert_eq!(span.value, "code");
+        assert_eq!(span.span, Span::with(Position::with(1, 1), Position::with(1, 7)));
+        let _ = text.next();
+        assert_eq!(text.scan_code_span().unwrap().value, "a``b");
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_code_span_unterminated() {
+        let mut text = Text::with_str("``a`b");
+
+        assert_eq!(text.scan_code_span(), None);
+        assert_eq!(text.position(), &Position::with(1, 1));
+    }
+
+    #[test]
+    fn scan_html_comment_body() {
+        let mut text = Text::with_str("<!-- first\nsecond -->x");
+        text.consume_bytes(4);
+
+        let body = text.scan_html_comment_body().unwrap();
+        assert_eq!(body.value, " first\nsecond ");
+        assert_eq!(body.span, Span::with(Position::with(1, 5), Position::with(2, 8)));
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_html_comment_body_unterminated() {
+        let mut text = Text::with_str("<!-- a\n-- >");
+        text.consume_bytes(4);
+
+        assert_eq!(text.scan_html_comment_body(),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 5), at: Position::with(2, 5) }));
+    }
+
+    #[test]
+    fn scan_md_link() {
+        let mut text = Text::with_str("[a \\] [b]](https://x.org/a_(b)) [c](<d e>)");
+
+        let link = text.scan_md_link().unwrap().unwrap();
+        assert_eq!(link.value, ("a ] [b]".to_string(), "https://x.org/a_(b)".to_string()));
+        assert_eq!(link.span, Span::with(Position::with(1, 1), Position::with(1, 32)));
+        assert_eq!(text.scan_md_link(), None);
+        let _ = text.next();
+        assert_eq!(text.scan_md_link().unwrap().unwrap().value, ("c".to_string(), "d e".to_string()));
+    }
+
+    #[test]
+    fn scan_md_link_malformed() {
+        let mut text = Text::with_str("[text] (url)");
+        assert_eq!(text.scan_md_link(),
+                   Some(Err(ScanError::ExpectedChar{ expected: '(', found: Some(' '), at: Position::with(1, 7) })));
+
+        let mut text = Text::with_str("[text](a b)");
+        assert_eq!(text.scan_md_link(),
+                   Some(Err(ScanError::ExpectedChar{ expected: ')', found: Some('b'), at: Position::with(1, 10) })));
+    }
+
+    #[test]
+    fn scan_html_entity() {
+        let mut text = Text::with_str("&amp;&#65;&#x41;&hellip;x");
+
+        let entity = text.scan_html_entity().unwrap().unwrap();
+        assert_eq!(entity.value, "&");
+        assert_eq!(entity.span, Span::with(Position::with(1, 1), Position::with(1, 6)));
+        assert_eq!(text.scan_html_entity().unwrap().unwrap().value, "A");
+        let entity = text.scan_html_entity().unwrap().unwrap();
+        assert_eq!(entity.value, "A");
+        assert_eq!(entity.span, Span::with(Position::with(1, 11), Position::with(1, 17)));
+        assert_eq!(text.scan_html_entity().unwrap().unwrap().value, "…");
+        assert_eq!(text.scan_html_entity(), None);
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_html_entity_errors() {
+        let mut text = Text::with_str("&amp");
+        assert_eq!(text.scan_html_entity(),
+                   Some(Err(ScanError::ExpectedChar{ expected: ';', found: None, at: Position::with(1, 5) })));
+
+        let mut text = Text::with_str("&bogus;");
+        assert_eq!(text.scan_html_entity(), Some(Err(ScanError::UnknownEntity{ at: Position::with(1, 1) })));
+
+        let mut text = Text::with_str("&#xD800;");
+        assert_eq!(text.scan_html_entity(),
+                   Some(Err(ScanError::InvalidCodePoint{ value: 0xd800, at: Position::with(1, 1) })));
+    }
+}
@@ -0,0 +1,403 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ident::ident_len;
+use crate::{ScanError, Spanned, Text};
+
+/// Returns the number of leading ASCII digits in `s`.
+fn digits_len(s: &str) -> usize {
+    s.bytes().take_while(u8::is_ascii_digit).count()
+}
+
+/// Returns the length in bytes of the exponent (e.g. `e-3`) at the start of `s` or 0 if `s`
+/// does not start with an exponent.
+fn exponent_len(s: &str) -> usize {
+    if !s.starts_with(['e', 'E']) {
+        return 0;
+    }
+    let sign = usize::from(s[1..].starts_with(['+', '-']));
+    match digits_len(&s[1 + sign..]) {
+        0 => 0,
+        digits => 1 + sign + digits,
+    }
+}
+
+/// Returns the length in bytes of the unsigned decimal number at the start of `s` or 0 if `s`
+/// does not start with a digit. The number consists of an integer part, an optional fractional
+/// part (a `.` followed by at least one digit) and an optional exponent.
+pub(crate) fn number_len(s: &str) -> usize {
+    let mut len = digits_len(s);
+    if len == 0 {
+        return 0;
+    }
+    if s[len..].starts_with('.') {
+        let fraction = digits_len(&s[len + 1..]);
+        if fraction > 0 {
+            len += 1 + fraction;
+        }
+    }
+    len + exponent_len(&s[len..])
+}
+
+/// Returns the length in bytes of the floating point number at the start of `s` or 0 if `s`
+/// does not start with one. In addition to `number_len()` an optional sign is accepted and the
+/// integer part may be omitted (e.g. `-.5`).
+pub(crate) fn float_len(s: &str) -> usize {
+    let sign = usize::from(s.starts_with(['+', '-']));
+    let rest = &s[sign..];
+    let len = match number_len(rest) {
+        0 if rest.starts_with('.') => match digits_len(&rest[1..]) {
+            0 => 0,
+            fraction => 1 + fraction + exponent_len(&rest[1 + fraction..]),
+        },
+        len => len,
+    };
+    if len == 0 { 0 } else { sign + len }
+}
+
+/// Special floating point values recognized by `Text::scan_float_or_special()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecialFloat {
+    /// `inf` or `+inf`
+    Inf,
+    /// `-inf`
+    NegInf,
+    /// `nan`
+    Nan,
+}
+
+/// Floating point value scanned by `Text::scan_float_or_special()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FloatValue<'a> {
+    /// the text of a finite number including its sign
+    Finite(&'a str),
+    /// one of the special values
+    Special(SpecialFloat),
+}
+
+/// Returns the special float value at the start of `s` and its length in bytes. The keywords
+/// are matched case-insensitively and must not be followed by an identifier char.
+fn special_float(s: &str) -> Option<(SpecialFloat, usize)> {
+    let sign = usize::from(s.starts_with(['+', '-']));
+    let keyword = s.get(sign..sign + 3)?;
+    let value = if keyword.eq_ignore_ascii_case("inf") {
+        if s.starts_with('-') { SpecialFloat::NegInf } else { SpecialFloat::Inf }
+    }
+    else if keyword.eq_ignore_ascii_case("nan") {
+        SpecialFloat::Nan
+    }
+    else {
+        return None;
+    };
+    match s[sign + 3..].chars().next() {
+        Some(ch) if ch.is_alphanumeric() || ch == '_' => None,
+        _ => Some((value, sign + 3)),
+    }
+}
+
+/// Interval in math notation like `[0, 1)` scanned by `Text::scan_interval()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interval<'a> {
+    /// the text of the lower bound
+    pub lower: &'a str,
+    /// the text of the upper bound
+    pub upper: &'a str,
+    /// `true` if the lower bound belongs to the interval (`[`)
+    pub lower_inclusive: bool,
+    /// `true` if the upper bound belongs to the interval (`]`)
+    pub upper_inclusive: bool,
+}
+
+impl<'a> Text<'a> {
+    /// Scans a bound of an interval surrounded by optional whitespace.
+    fn scan_interval_bound(&mut self) -> Result<&'a str, ScanError> {
+        self.consume_while_on_line(char::is_whitespace);
+        let rest = self.remaining();
+        let len = special_float(rest).map_or_else(|| float_len(rest), |(_, len)| len);
+        if len == 0 {
+            return Err(ScanError::Unexpected{ expected: "bound", found: self.peek_next(), at: self.position.clone() });
+        }
+        let bound = self.consume_bytes(len);
+        self.consume_while_on_line(char::is_whitespace);
+        Ok(bound)
+    }
+
+    /// Scans an interval in math notation like `[0, 1)` or `(-inf, 2.5]` where `[` and `]` mark
+    /// closed ends and `(` and `)` open ends. The bounds are numbers as accepted by
+    /// `scan_float_or_special()`.
+    /// Returns `None` without consuming anything if the next char is neither `[` nor `(` and an
+    /// error if the interval is malformed after that.
+    pub fn scan_interval(&mut self) -> Option<Result<Spanned<Interval<'a>>, ScanError>> {
+        let lower_inclusive = match self.peek_next()? {
+            '[' => true,
+            '(' => false,
+            _ => return None,
+        };
+        Some(self.scan_interval_committed(lower_inclusive))
+    }
+
+    /// Scans the rest of an interval after `scan_interval()` has checked the opening bracket.
+    fn scan_interval_committed(&mut self, lower_inclusive: bool) -> Result<Spanned<Interval<'a>>, ScanError> {
+        let start = self.position.clone();
+        let _ = self.next();
+        let lower = self.scan_interval_bound()?;
+        self.expect_char(',')?;
+        let upper = self.scan_interval_bound()?;
+        let upper_inclusive = match self.peek_next() {
+            Some(']') => true,
+            Some(')') => false,
+            found => return Err(ScanError::ExpectedClose{ opener_at: Some(start), found, at: self.position.clone() }),
+        };
+        let _ = self.next();
+        Ok(self.spanned(start, Interval{ lower, upper, lower_inclusive, upper_inclusive }))
+    }
+
+    /// Scans an unsigned decimal number (integer part, optional fraction and exponent) followed
+    /// by an optional type suffix like in `42u8` or `3.0f32`. The suffix is an identifier that
+    /// immediately follows the number. Returns the number and the suffix; the span covers both.
+    pub fn scan_number_suffixed(&mut self) -> Option<Spanned<(&'a str, Option<&'a str>)>> {
+        let rest = self.remaining();
+        let len = number_len(rest);
+        if len == 0 {
+            return None;
+        }
+        let suffix_len = ident_len(&rest[len..], &[], &[]);
+
+        let start = self.position.clone();
+        let number = self.consume_bytes(len);
+        let suffix = if suffix_len > 0 { Some(self.consume_bytes(suffix_len)) } else { None };
+        Some(self.spanned(start, (number, suffix)))
+    }
+
+    /// Scans an integer whose digits are grouped by `group_sep` like `1,000,000`. Every group
+    /// after the first must have exactly `group_size` digits and the first group must not have
+    /// more than `group_size` digits if there are further groups. A separator that is not
+    /// followed by a digit is not part of the number.
+    /// Returns `ScanError::BadGrouping` for a group of wrong size without consuming anything.
+    pub fn scan_grouped_integer(&mut self, group_sep: char, group_size: usize) -> Result<Spanned<&'a str>, ScanError> {
+        let rest = self.remaining();
+        let first = digits_len(rest);
+        if first == 0 {
+            return Err(ScanError::Unexpected{ expected: "digit", found: self.peek_next(), at: self.position.clone() });
+        }
+        let mut len = first;
+        while let Some(group) = rest[len..].strip_prefix(group_sep) {
+            let digits = digits_len(group);
+            if digits == 0 {
+                break;
+            }
+            if digits != group_size || first > group_size {
+                let at = if first > group_size { 0 } else { len + group_sep.len_utf8() };
+                return Err(ScanError::BadGrouping{ at: self.position_after(at) });
+            }
+            len += group_sep.len_utf8() + digits;
+        }
+
+        let start = self.position.clone();
+        let number = self.consume_bytes(len);
+        Ok(self.spanned(start, number))
+    }
+
+    /// Scans one of the special values `inf`, `-inf` and `nan` (case-insensitive, optionally
+    /// signed) or an ordinary floating point number with optional sign.
+    /// A keyword that is only the prefix of an identifier (like `infinity_bound`) does not match.
+    /// Returns `None` without consuming anything if neither matches.
+    pub fn scan_float_or_special(&mut self) -> Option<Spanned<FloatValue<'a>>> {
+        let rest = self.remaining();
+        let start = self.position.clone();
+        if let Some((special, len)) = special_float(rest) {
+            self.consume_bytes(len);
+            return Some(self.spanned(start, FloatValue::Special(special)));
+        }
+        match float_len(rest) {
+            0 => None,
+            len => {
+                let number = self.consume_bytes(len);
+                Some(self.spanned(start, FloatValue::Finite(number)))
+            },
+        }
+    }
+
+    /// Scans a floating point number with optional sign, optional integer part, optional
+    /// fractional part and optional exponent (e.g. `3.14`, `-2e10`, `.5`) and returns its value.
+    /// Returns `None` without consuming anything if no number starts at the reading position and
+    /// `ScanError::Overflow` (after consuming the number) if its value is not finite.
+    pub fn scan_f64(&mut self) -> Option<Result<Spanned<f64>, ScanError>> {
+        let len = float_len(self.remaining());
+        if len == 0 {
+            return None;
+        }
+        let start = self.position.clone();
+        let value: f64 = self.remaining()[..len].parse().expect("float_len accepts only parseable floats");
+        self.consume_bytes(len);
+        if value.is_infinite() {
+            return Some(Err(ScanError::Overflow{ at: start }));
+        }
+        Some(Ok(self.spanned(start, value)))
+    }
+
+    /// Scans exactly `n` digits of the given `radix` and returns their value.
+    /// When fewer than `n` digits are available, nothing is consumed and
+    /// `ScanError::ExpectedDigits` is returned.
+    ///
+    /// # Panics
+    /// Panics if `radix` is not in the range from 2 to 36.
+    pub fn scan_fixed_digits(&mut self, n: usize, radix: u32) -> Result<Spanned<u32>, ScanError> {
+        let start = self.position.clone();
+        let digits: Vec<u32> = self.remaining().chars()
+            .take(n)
+            .map_while(|ch| ch.to_digit(radix))
+            .collect();
+        if digits.len() < n {
+            return Err(ScanError::ExpectedDigits{ at: start, needed: n });
+        }
+        let value = digits.iter()
+            .try_fold(0u32, |value, &digit| value.checked_mul(radix)?.checked_add(digit))
+            .ok_or_else(|| ScanError::Overflow{ at: start.clone() })?;
+
+        self.consume_bytes(n);
+        Ok(self.spanned(start, value))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_fixed_digits() {
+        let mut text = Text::with_str("00e9z");
+
+        let value = text.scan_fixed_digits(4, 16).unwrap();
+        assert_eq!(value.value, 0xe9);
+        assert_eq!(value.span, Span::with(Position::with(1, 1), Position::with(1, 5)));
+        assert_eq!(text.next(), Some('z'));
+    }
+
+    #[test]
+    fn scan_fixed_digits_too_few() {
+        let mut text = Text::with_str("0e9z");
+
+        assert_eq!(text.scan_fixed_digits(4, 16),
+                   Err(ScanError::ExpectedDigits{ at: Position::with(1, 1), needed: 4 }));
+        assert_eq!(text.next(), Some('0'));
+    }
+
+    #[test]
+    fn scan_number_suffixed() {
+        let mut text = Text::with_str("42u8 3.0f32 42 1e5");
+
+        let number = text.scan_number_suffixed().unwrap();
+        assert_eq!(number.value, ("42", Some("u8")));
+        assert_eq!(number.span, Span::with(Position::with(1, 1), Position::with(1, 5)));
+        let _ = text.next();
+        assert_eq!(text.scan_number_suffixed().unwrap().value, ("3.0", Some("f32")));
+        let _ = text.next();
+        assert_eq!(text.scan_number_suffixed().unwrap().value, ("42", None));
+        let _ = text.next();
+        assert_eq!(text.scan_number_suffixed().unwrap().value, ("1e5", None));
+        assert_eq!(text.scan_number_suffixed(), None);
+    }
+
+    #[test]
+    fn scan_grouped_integer() {
+        let mut text = Text::with_str("1,000,000, 12");
+
+        let number = text.scan_grouped_integer(',', 3).unwrap();
+        assert_eq!(number.value, "1,000,000");
+        assert_eq!(number.span, Span::with(Position::with(1, 1), Position::with(1, 10)));
+        assert_eq!(text.next(), Some(','));
+        let _ = text.next();
+        assert_eq!(text.scan_grouped_integer(',', 3).unwrap().value, "12");
+    }
+
+    #[test]
+    fn scan_grouped_integer_bad_grouping() {
+        let mut text = Text::with_str("1,00,000");
+        assert_eq!(text.scan_grouped_integer(',', 3), Err(ScanError::BadGrouping{ at: Position::with(1, 3) }));
+        assert_eq!(text.position(), &Position::with(1, 1));
+
+        let mut text = Text::with_str("1000,000");
+        assert_eq!(text.scan_grouped_integer(',', 3), Err(ScanError::BadGrouping{ at: Position::with(1, 1) }));
+    }
+
+    #[test]
+    fn scan_float_or_special() {
+        let mut text = Text::with_str("inf -INF nan -.5e3 infx");
+
+        let value = text.scan_float_or_special().unwrap();
+        assert_eq!(value.value, FloatValue::Special(SpecialFloat::Inf));
+        assert_eq!(value.span, Span::with(Position::with(1, 1), Position::with(1, 4)));
+        let _ = text.next();
+        assert_eq!(text.scan_float_or_special().unwrap().value, FloatValue::Special(SpecialFloat::NegInf));
+        let _ = text.next();
+        assert_eq!(text.scan_float_or_special().unwrap().value, FloatValue::Special(SpecialFloat::Nan));
+        let _ = text.next();
+        assert_eq!(text.scan_float_or_special().unwrap().value, FloatValue::Finite("-.5e3"));
+        let _ = text.next();
+        assert_eq!(text.scan_float_or_special(), None);
+        assert_eq!(text.next(), Some('i'));
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn scan_f64() {
+        let mut text = Text::with_str("3.14 -2e10 .5 x");
+
+        let value = text.scan_f64().unwrap().unwrap();
+        assert_eq!(value.value, 3.14);
+        assert_eq!(value.span, Span::with(Position::with(1, 1), Position::with(1, 5)));
+        let _ = text.next();
+        assert_eq!(text.scan_f64().unwrap().unwrap().value, -2e10);
+        let _ = text.next();
+        assert_eq!(text.scan_f64().unwrap().unwrap().value, 0.5);
+        let _ = text.next();
+        assert_eq!(text.scan_f64(), None);
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_f64_overflow() {
+        let mut text = Text::with_str("1e999;");
+
+        assert_eq!(text.scan_f64(), Some(Err(ScanError::Overflow{ at: Position::with(1, 1) })));
+        assert_eq!(text.next(), Some(';'));
+    }
+
+    #[test]
+    fn scan_interval() {
+        let mut text = Text::with_str("[0, 1) (-inf,2.5]");
+
+        let interval = text.scan_interval().unwrap().unwrap();
+        assert_eq!(interval.value, Interval{ lower: "0", upper: "1", lower_inclusive: true, upper_inclusive: false });
+        assert_eq!(interval.span, Span::with(Position::with(1, 1), Position::with(1, 7)));
+        assert_eq!(text.scan_interval(), None);
+        let _ = text.next();
+        assert_eq!(text.scan_interval().unwrap().unwrap().value,
+                   Interval{ lower: "-inf", upper: "2.5", lower_inclusive: false, upper_inclusive: true });
+    }
+
+    #[test]
+    fn scan_interval_malformed() {
+        let mut text = Text::with_str("[0 1]");
+        assert_eq!(text.scan_interval(),
+                   Some(Err(ScanError::ExpectedChar{ expected: ',', found: Some('1'), at: Position::with(1, 4) })));
+
+        let mut text = Text::with_str("[0, 1");
+        assert_eq!(text.scan_interval(),
+                   Some(Err(ScanError::ExpectedClose{ opener_at: Some(Position::with(1, 1)), found: None, at: Position::with(1, 6) })));
+    }
+}
@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Spanned, Text};
+
+/// Node of the trie within an `OperatorSet`.
+#[derive(Clone, Debug, Default)]
+struct Node {
+    children: Vec<(char, usize)>,
+    terminal: bool,
+}
+
+/// Set of operators compiled into a trie, so that `Text::scan_operator_set()` can find the
+/// longest matching operator with a single pass over the input.
+#[derive(Clone, Debug)]
+pub struct OperatorSet {
+    nodes: Vec<Node>,
+}
+
+impl OperatorSet {
+    /// Creates a new `OperatorSet` containing the given operators.
+    pub fn with_operators(operators: &[&str]) -> Self {
+        let mut set = OperatorSet{ nodes: vec![Node::default()] };
+        for operator in operators {
+            set.insert(operator);
+        }
+        set
+    }
+
+    /// Adds `operator` to the set.
+    pub fn insert(&mut self, operator: &str) {
+        let mut node = 0;
+        for ch in operator.chars() {
+            node = match self.nodes[node].children.iter().find(|&&(c, _)| c == ch) {
+                Some(&(_, child)) => child,
+                None => {
+                    self.nodes.push(Node::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.push((ch, child));
+                    child
+                },
+            };
+        }
+        self.nodes[node].terminal = !operator.is_empty();
+    }
+
+    /// Returns the length in bytes of the longest operator at the start of `s` or 0 if no
+    /// operator matches.
+    fn longest_match(&self, s: &str) -> usize {
+        let mut node = 0;
+        let mut len = 0;
+        for (index, ch) in s.char_indices() {
+            match self.nodes[node].children.iter().find(|&&(c, _)| c == ch) {
+                Some(&(_, child)) => node = child,
+                None => break,
+            }
+            if self.nodes[node].terminal {
+                len = index + ch.len_utf8();
+            }
+        }
+        len
+    }
+}
+
+/// Assignment and comparison operators scanned by `Text::scan_assign_or_compare()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssignOp {
+    /// `=`
+    Assign,
+    /// `+=`
+    PlusEq,
+    /// `-=`
+    MinusEq,
+    /// `*=`
+    StarEq,
+    /// `/=`
+    SlashEq,
+    /// `%=`
+    PercentEq,
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+}
+
+const ASSIGN_OPS: &[(&str, AssignOp)] = &[
+    ("=", AssignOp::Assign), ("+=", AssignOp::PlusEq), ("-=", AssignOp::MinusEq),
+    ("*=", AssignOp::StarEq), ("/=", AssignOp::SlashEq), ("%=", AssignOp::PercentEq),
+    ("==", AssignOp::Eq), ("!=", AssignOp::Ne), ("<", AssignOp::Lt), ("<=", AssignOp::Le),
+    (">", AssignOp::Gt), (">=", AssignOp::Ge),
+];
+
+impl<'a> Text<'a> {
+    /// Scans the longest assignment or comparison operator (see `AssignOp`) that starts at the
+    /// reading position, so `==` is scanned as `AssignOp::Eq` and not as two `AssignOp::Assign`.
+    /// Operators outside of this set are not recognized as a whole, e.g. for `=>` only the `=` is
+    /// scanned as `AssignOp::Assign` and for `<<` only the first `<` as `AssignOp::Lt`.
+    /// Returns `None` without consuming anything if no operator matches.
+    pub fn scan_assign_or_compare(&mut self) -> Option<Spanned<AssignOp>> {
+        let rest = self.remaining();
+        let &(operator, op) = ASSIGN_OPS.iter()
+            .filter(|(operator, _)| rest.starts_with(operator))
+            .max_by_key(|(operator, _)| operator.len())?;
+        let start = self.position.clone();
+        self.consume_bytes(operator.len());
+        Some(self.spanned(start, op))
+    }
+
+    /// Scans the longest of the given operators that starts at the reading position.
+    /// Returns `None` without consuming anything if no operator matches.
+    pub fn scan_operator(&mut self, operators: &[&str]) -> Option<Spanned<&'a str>> {
+        let rest = self.remaining();
+        let len = operators.iter()
+            .filter(|operator| rest.starts_with(*operator))
+            .map(|operator| operator.len())
+            .max()
+            .filter(|&len| len > 0)?;
+        let start = self.position.clone();
+        let operator = self.consume_bytes(len);
+        Some(self.spanned(start, operator))
+    }
+
+    /// Scans the longest of the given operators that starts at the reading position and that is
+    /// accepted by `guard`. `guard` receives the operator and the char following it (`None` at
+    /// EOF); if it rejects the operator, the next shorter matching operator is tried. This allows
+    /// e.g. to scan `..` only if it is not followed by `=`.
+    /// Returns `None` without consuming anything if no operator matches and is accepted.
+    pub fn scan_operator_guarded(&mut self, operators: &[&str], guard: impl Fn(&str, Option<char>) -> bool) -> Option<Spanned<&'a str>> {
+        let rest = self.remaining();
+        let mut candidates: Vec<&str> = operators.iter()
+            .copied()
+            .filter(|operator| !operator.is_empty() && rest.starts_with(operator))
+            .collect();
+        candidates.sort_by_key(|operator| std::cmp::Reverse(operator.len()));
+        let len = candidates.into_iter()
+            .find(|operator| guard(operator, rest[operator.len()..].chars().next()))?
+            .len();
+        let start = self.position.clone();
+        let operator = self.consume_bytes(len);
+        Some(self.spanned(start, operator))
+    }
+
+    /// Scans the longest operator of `operators` that starts at the reading position.
+    /// Returns `None` without consuming anything if no operator matches.
+    pub fn scan_operator_set(&mut self, operators: &OperatorSet) -> Option<Spanned<&'a str>> {
+        let len = operators.longest_match(self.remaining());
+        if len == 0 {
+            return None;
+        }
+        let start = self.position.clone();
+        let operator = self.consume_bytes(len);
+        Some(self.spanned(start, operator))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_operator_set() {
+        let operators = OperatorSet::with_operators(&["<", "<<", "<<=", "="]);
+        let mut text = Text::with_str("<<=<<<=x");
+
+        let operator = text.scan_operator_set(&operators).unwrap();
+        assert_eq!(operator.value, "<<=");
+        assert_eq!(operator.span, Span::with(Position::with(1, 1), Position::with(1, 4)));
+        assert_eq!(text.scan_operator_set(&operators).unwrap().value, "<<");
+        assert_eq!(text.scan_operator_set(&operators).unwrap().value, "<");
+        assert_eq!(text.scan_operator_set(&operators).unwrap().value, "=");
+        assert_eq!(text.scan_operator_set(&operators), None);
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_operator_guarded() {
+        let operators = [".", "..", "..="];
+        let no_range_before_eq = |operator: &str, next: Option<char>| operator != ".." || next != Some('=');
+
+        let mut text = Text::with_str("..=");
+        assert_eq!(text.scan_operator_guarded(&operators, |_, _| true).unwrap().value, "..=");
+
+        let mut text = Text::with_str("..=");
+        let operator = text.scan_operator_guarded(&[".", ".."], no_range_before_eq).unwrap();
+        assert_eq!(operator.value, ".");
+        assert_eq!(operator.span, Span::with(Position::with(1, 1), Position::with(1, 2)));
+
+        let mut text = Text::with_str("..x");
+        assert_eq!(text.scan_operator_guarded(&operators, no_range_before_eq).unwrap().value, "..");
+        assert_eq!(text.scan_operator_guarded(&operators, |_, _| false), None);
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_assign_or_compare() {
+        let mut text = Text::with_str("= == += => x");
+
+        let op = text.scan_assign_or_compare().unwrap();
+        assert_eq!(op.value, AssignOp::Assign);
+        assert_eq!(op.span, Span::with(Position::with(1, 1), Position::with(1, 2)));
+        let _ = text.next();
+        let op = text.scan_assign_or_compare().unwrap();
+        assert_eq!(op.value, AssignOp::Eq);
+        assert_eq!(op.span, Span::with(Position::with(1, 3), Position::with(1, 5)));
+        let _ = text.next();
+        assert_eq!(text.scan_assign_or_compare().unwrap().value, AssignOp::PlusEq);
+        let _ = text.next();
+        assert_eq!(text.scan_assign_or_compare().unwrap().value, AssignOp::Assign);
+        assert_eq!(text.scan_assign_or_compare().unwrap().value, AssignOp::Gt);
+        let _ = text.next();
+        assert_eq!(text.scan_assign_or_compare(), None);
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn scan_operator() {
+        let mut text = Text::with_str("<<<=");
+
+        assert_eq!(text.scan_operator(&["<", "<<", "<<="]).unwrap().value, "<<");
+        assert_eq!(text.scan_operator(&["<", "<<", "<<="]).unwrap().value, "<");
+        assert_eq!(text.scan_operator(&[">"]), None);
+    }
+}
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{is_line_terminator, ScanError, Text};
+
+impl<'a> Text<'a> {
+    /// Consumes characters while `pred` holds, but never beyond the end of the current line.
+    /// Returns the consumed slice.
+    pub fn consume_while_on_line<F: Fn(char) -> bool>(&mut self, pred: F) -> &'a str {
+        let start = self.offset();
+        while let Some(ch) = self.peek_next() {
+            if is_line_terminator(ch) || !pred(ch) {
+                break;
+            }
+            let _ = self.next();
+        }
+        &self.source[start..self.offset()]
+    }
+
+    /// Consumes characters while `pred` holds and returns them mapped by `transform`.
+    pub fn consume_while_map<F: Fn(char) -> bool>(&mut self, pred: F, transform: impl Fn(char) -> char) -> String {
+        let mut mapped = String::new();
+        while let Some(ch) = self.peek_next() {
+            if !pred(ch) {
+                break;
+            }
+            mapped.push(transform(ch));
+            let _ = self.next();
+        }
+        mapped
+    }
+
+    /// Consumes characters up to the first one of `stop` (or EOF) and returns them. The stop
+    /// char is not consumed.
+    pub fn consume_until_any(&mut self, stop: &[char]) -> &'a str {
+        let rest = self.remaining();
+        self.consume_bytes(rest.find(stop).unwrap_or(rest.len()))
+    }
+
+    /// Consumes `target` if it is next and not followed by one of `not_following`, e.g. a `/`
+    /// that does not start a comment. Returns `true` if `target` was consumed.
+    pub fn consume_if_not_followed_by(&mut self, target: char, not_following: &[char]) -> bool {
+        let mut chars = self.remaining().chars();
+        if chars.next() != Some(target) || chars.next().is_some_and(|ch| not_following.contains(&ch)) {
+            return false;
+        }
+        let _ = self.next();
+        true
+    }
+
+    /// Consumes and returns the next char if it lies within one of the inclusive `ranges`.
+    /// Returns `None` without consuming anything otherwise.
+    pub fn consume_in_ranges(&mut self, ranges: &[(char, char)]) -> Option<char> {
+        let ch = self.peek_next()?;
+        if !ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&ch)) {
+            return None;
+        }
+        self.next()
+    }
+
+    /// Consumes at most `max` characters while `pred` holds and returns them. If fewer than `min`
+    /// characters match, nothing is consumed and `ScanError::TooFew` is returned.
+    pub fn consume_between<F: Fn(char) -> bool>(&mut self, min: usize, max: usize, pred: F) -> Result<&'a str, ScanError> {
+        let rest = self.remaining();
+        let (count, len) = rest.chars()
+            .take(max)
+            .take_while(|&ch| pred(ch))
+            .fold((0, 0), |(count, len), ch| (count + 1, len + ch.len_utf8()));
+        if count < min {
+            return Err(ScanError::TooFew{ at: self.position.clone(), needed: min });
+        }
+        Ok(self.consume_bytes(len))
+    }
+
+    /// Consumes everything up to the next occurrence of the ASCII `byte` and returns it, leaving
+    /// the delimiter unconsumed. Consumes the rest of the text if `byte` does not occur.
+    /// The delimiter is located by a byte search instead of decoding characters.
+    ///
+    /// # Panics
+    /// Panics if `byte` is not ASCII.
+    pub fn scan_until_byte(&mut self, byte: u8) -> &'a str {
+        assert!(byte.is_ascii(), "scan_until_byte requires an ASCII delimiter, got {byte:#04x}");
+        let rest = self.as_bytes_remaining();
+        let len = rest.iter().position(|&b| b == byte).unwrap_or(rest.len());
+        self.consume_bytes(len)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn consume_while_on_line() {
+        let mut text = Text::with_str("token\nnext line");
+
+        assert_eq!(text.consume_while_on_line(|ch| ch != ' '), "token");
+        assert_eq!(text.position(), &Position::with(1, 6));
+        assert_eq!(text.peek_next(), Some('\n'));
+    }
+
+    #[test]
+    fn consume_while_map() {
+        let mut text = Text::with_str("ABC1");
+
+        assert_eq!(text.consume_while_map(|ch| ch.is_alphabetic(), |ch| ch.to_ascii_lowercase()), "abc");
+        assert_eq!(text.position(), &Position::with(1, 4));
+        assert_eq!(text.next(), Some('1'));
+    }
+
+    #[test]
+    fn consume_until_any() {
+        let mut text = Text::with_str("abc;d\nef,g");
+
+        assert_eq!(text.consume_until_any(&[';', ',']), "abc");
+        assert_eq!(text.next(), Some(';'));
+        assert_eq!(text.consume_until_any(&[';', ',']), "d\nef");
+        assert_eq!(text.position(), &Position::with(2, 3));
+        let _ = text.next();
+        assert_eq!(text.consume_until_any(&[';', ',']), "g");
+        assert_eq!(text.next(), None);
+    }
+
+    #[test]
+    fn consume_if_not_followed_by() {
+        let mut text = Text::with_str("/x");
+        assert!(text.consume_if_not_followed_by('/', &['/', '*']));
+        assert_eq!(text.next(), Some('x'));
+
+        let mut text = Text::with_str("///");
+        assert!(!text.consume_if_not_followed_by('/', &['/', '*']));
+        assert!(!text.consume_if_not_followed_by('x', &[]));
+        assert_eq!(text.position(), &Position::with(1, 1));
+        text.consume_bytes(2);
+        assert!(text.consume_if_not_followed_by('/', &['/', '*']));
+    }
+
+    #[test]
+    fn consume_in_ranges() {
+        let ranges = [('a', 'z'), ('0', '9')];
+        let mut text = Text::with_str("m7-");
+
+        assert_eq!(text.consume_in_ranges(&ranges), Some('m'));
+        assert_eq!(text.consume_in_ranges(&ranges), Some('7'));
+        assert_eq!(text.consume_in_ranges(&ranges), None);
+        assert_eq!(text.next(), Some('-'));
+    }
+
+    #[test]
+    fn consume_between() {
+        let digit = |ch: char| ch.is_ascii_digit();
+
+        let mut text = Text::with_str("1,234,5678");
+        assert_eq!(text.consume_between(1, 3, digit), Ok("1"));
+        let _ = text.next();
+        assert_eq!(text.consume_between(1, 3, digit), Ok("234"));
+        let _ = text.next();
+        assert_eq!(text.consume_between(1, 3, digit), Ok("567"));
+        assert_eq!(text.position(), &Position::with(1, 10));
+
+        let mut text = Text::with_str("1x");
+        assert_eq!(text.consume_between(2, 3, digit), Err(ScanError::TooFew{ at: Position::with(1, 1), needed: 2 }));
+        assert_eq!(text.next(), Some('1'));
+    }
+
+    #[test]
+    fn scan_until_byte() {
+        let mut text = Text::with_str("größe\nü;rest");
+
+        assert_eq!(text.scan_until_byte(b';'), "größe\nü");
+        assert_eq!(text.position(), &Position::with(2, 2));
+        assert_eq!(text.next(), Some(';'));
+        assert_eq!(text.scan_until_byte(b';'), "rest");
+        assert_eq!(text.next(), None);
+    }
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{is_line_terminator, ScanError, Spanned, Text};
+
+impl<'a> Text<'a> {
+    /// Returns the next char unless it terminates the line.
+    fn next_on_line(&mut self) -> Option<char> {
+        match self.peek_next() {
+            Some(ch) if !is_line_terminator(ch) => self.next(),
+            _ => None,
+        }
+    }
+
+    /// Splits the rest of the current line into shell arguments separated by whitespace and
+    /// returns the decoded arguments. Like in POSIX shells, single quotes keep everything
+    /// literally, within double quotes a backslash only escapes `"`, `\`, `$` and `` ` ``, and
+    /// outside of quotes a backslash escapes any char. The line terminator is not consumed.
+    /// Returns `ScanError::Unterminated` if a quote is not closed on the line.
+    pub fn scan_argv(&mut self) -> Result<Vec<Spanned<String>>, ScanError> {
+        let mut args = Vec::new();
+        loop {
+            self.consume_while_on_line(char::is_whitespace);
+            let start = self.position.clone();
+            let mut arg = String::new();
+            let mut is_arg = false;
+            while let Some(ch) = self.peek_next() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                is_arg = true;
+                let quote_at = self.position.clone();
+                let _ = self.next();
+                let unterminated = |text: &Text| ScanError::Unterminated{ start: quote_at.clone(), at: text.position.clone() };
+                match ch {
+                    '\'' => loop {
+                        match self.next_on_line() {
+                            Some('\'') => break,
+                            Some(ch) => arg.push(ch),
+                            None => return Err(unterminated(self)),
+                        }
+                    },
+                    '"' => loop {
+                        match self.next_on_line() {
+                            Some('"') => break,
+                            Some('\\') => match self.peek_next() {
+                                Some(ch @ ('"' | '\\' | '$' | '`')) => {
+                                    let _ = self.next();
+                                    arg.push(ch);
+                                },
+                                _ => arg.push('\\'),
+                            },
+                            Some(ch) => arg.push(ch),
+                            None => return Err(unterminated(self)),
+                        }
+                    },
+                    '\\' => arg.push(self.next_on_line().unwrap_or('\\')),
+                    ch => arg.push(ch),
+                }
+            }
+            if !is_arg {
+                break;
+            }
+            args.push(self.spanned(start, arg));
+        }
+        Ok(args)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_argv() {
+        let mut text = Text::with_str("a 'b c' \"d\\\"e\"\\ f\nnext");
+
+        let args = text.scan_argv().unwrap();
+        assert_eq!(args, vec![
+            Spanned::with("a".to_string(), Span::with(Position::with(1, 1), Position::with(1, 2))),
+            Spanned::with("b c".to_string(), Span::with(Position::with(1, 3), Position::with(1, 8))),
+            Spanned::with("d\"e f".to_string(), Span::with(Position::with(1, 9), Position::with(1, 18))),
+        ]);
+        assert_eq!(text.peek_next(), Some('\n'));
+    }
+
+    #[test]
+    fn scan_argv_unterminated() {
+        let mut text = Text::with_str("echo \"it's\nx\"");
+
+        assert_eq!(text.scan_argv(), Err(ScanError::Unterminated{ start: Position::with(1, 6), at: Position::with(1, 11) }));
+    }
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::Position;
+
+/// Range within a text given by the positions of its first and its end character.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// position of the first character within the span
+    pub start: Position,
+    /// position of the first character AFTER the span
+    pub end: Position,
+}
+
+impl Span {
+    /// Creates a new `Span` from `start` up to (excluding) `end`.
+    pub fn with( start: Position, end: Position ) -> Self {
+        Span{ start, end }
+    }
+
+    /// Creates an empty `Span` located at the given position.
+    pub fn empty_at( position: Position ) -> Self {
+        Span{ start: position.clone(), end: position }
+    }
+
+    /// Returns `true` when the span does not cover any character.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// A scanned value together with the `Span` of the text it was scanned from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spanned<T> {
+    /// the scanned value
+    pub value: T,
+    /// the text range covered by the value
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    /// Creates a new `Spanned` for the given value and span.
+    pub fn with( value: T, span: Span ) -> Self {
+        Spanned{ value, span }
+    }
+}
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ident::{ident_len, starts_with_keyword};
+use crate::{ScanError, Spanned, Text};
+
+impl<'a> Text<'a> {
+    /// Scans one part of an SQL identifier: a bare identifier or a name quoted by `"` or `` ` ``
+    /// where a doubled quote stands for the quote itself.
+    fn scan_sql_ident_part(&mut self) -> Result<String, ScanError> {
+        let quote = match self.peek_next() {
+            Some(quote @ ('"' | '`')) => quote,
+            _ => return self.expect_ident().map(str::to_string),
+        };
+        let start = self.position.clone();
+        let _ = self.next();
+        let mut part = String::new();
+        loop {
+            match self.next() {
+                Some(ch) if ch == quote => {
+                    if self.peek_next() != Some(quote) {
+                        return Ok(part);
+                    }
+                    let _ = self.next();
+                    part.push(quote);
+                },
+                Some(ch) => part.push(ch),
+                None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+            }
+        }
+    }
+
+    /// Scans an SQL identifier like `schema."My Table"` consisting of `.` separated parts that
+    /// are either bare identifiers or quoted by `"` or `` ` `` (with a doubled quote standing for
+    /// the quote itself) and returns the decoded parts.
+    pub fn scan_sql_ident(&mut self) -> Result<Spanned<Vec<String>>, ScanError> {
+        let start = self.position.clone();
+        let mut parts = vec![self.scan_sql_ident_part()?];
+        while self.peek_next() == Some('.') {
+            let _ = self.next();
+            parts.push(self.scan_sql_ident_part()?);
+        }
+        Ok(self.spanned(start, parts))
+    }
+
+    /// Consumes a clause up to the next of `keywords` (ignoring ASCII case and only as whole
+    /// words) outside of any pair of `open` and `close` and returns it. The keyword is not
+    /// consumed. A `close` without matching `open` and EOF also end the clause.
+    pub fn scan_clause_until_keywords(&mut self, keywords: &[&str], open: char, close: char) -> &'a str {
+        let start = self.offset();
+        let mut depth = 0usize;
+        while let Some(ch) = self.peek_next() {
+            let rest = self.remaining();
+            let word = ident_len(rest, &[], &[]);
+            if word > 0 {
+                if depth == 0 && starts_with_keyword(rest, keywords) {
+                    break;
+                }
+                self.consume_bytes(word);
+                continue;
+            }
+            if ch.is_alphanumeric() {
+                self.consume_while_on_line(|ch| ch.is_alphanumeric() || ch == '_');
+                continue;
+            }
+            if ch == open {
+                depth += 1;
+            }
+            else if ch == close {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            }
+            let _ = self.next();
+        }
+        &self.source[start..self.offset()]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_sql_ident() {
+        let mut text = Text::with_str("table \"My \"\"Table\"\"\" `schema`.`tbl`.col");
+
+        let ident = text.scan_sql_ident().unwrap();
+        assert_eq!(ident.value, vec!["table"]);
+        assert_eq!(ident.span, Span::with(Position::with(1, 1), Position::with(1, 6)));
+        let _ = text.next();
+        let ident = text.scan_sql_ident().unwrap();
+        assert_eq!(ident.value, vec!["My \"Table\""]);
+        assert_eq!(ident.span, Span::with(Position::with(1, 7), Position::with(1, 21)));
+        let _ = text.next();
+        let ident = text.scan_sql_ident().unwrap();
+        assert_eq!(ident.value, vec!["schema", "tbl", "col"]);
+        assert_eq!(ident.span, Span::with(Position::with(1, 22), Position::with(1, 40)));
+    }
+
+    #[test]
+    fn scan_sql_ident_errors() {
+        let mut text = Text::with_str("\"open");
+        assert_eq!(text.scan_sql_ident(), Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(1, 6) }));
+
+        let mut text = Text::with_str("a.1");
+        assert_eq!(text.scan_sql_ident(),
+                   Err(ScanError::Unexpected{ expected: "identifier", found: Some('1'), at: Position::with(1, 3) }));
+    }
+
+    #[test]
+    fn scan_clause_until_keywords() {
+        let mut text = Text::with_str("SELECT a FROM (SELECT b WHERE c) t, somewhere where x");
+
+        let _ = text.scan_ident_with(&[], &[]);
+        assert_eq!(text.scan_clause_until_keywords(&["where", "order by"], '(', ')'),
+                   " a FROM (SELECT b WHERE c) t, somewhere ");
+        assert_eq!(text.scan_ident_with(&[], &[]).unwrap().value, "where");
+
+        let mut text = Text::with_str("SELECT a FROM (SELECT WHERE) WHERE x");
+        assert_eq!(text.scan_clause_until_keywords(&["WHERE"], '(', ')'), "SELECT a FROM (SELECT WHERE) ");
+        assert_eq!(text.position(), &Position::with(1, 30));
+
+        let mut text = Text::with_str("a 1where b WHERE c");
+        assert_eq!(text.scan_clause_until_keywords(&["where"], '(', ')'), "a 1where b ");
+
+        let mut text = Text::with_str("x ORDER  BY y ORDER BY z");
+        assert_eq!(text.scan_clause_until_keywords(&["order by"], '(', ')'), "x ORDER  BY y ");
+    }
+}
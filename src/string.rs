@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{is_line_terminator, ScanError, Spanned, Text};
+
+impl<'a> Text<'a> {
+    /// Scans a string enclosed by the quote char that is next (e.g. `"` or `'`) and returns its
+    /// decoded content. Supported escapes are `\\`, `\"`, `\'`, `\n`, `\r`, `\t`, `\0`, `\uXXXX`
+    /// and `\UXXXXXXXX`. A line terminator or EOF before the closing quote is an error.
+    pub(crate) fn scan_escaped_string(&mut self) -> Result<Spanned<String>, ScanError> {
+        let start = self.position.clone();
+        let quote = match self.next() {
+            Some(quote) => quote,
+            None => return Err(ScanError::Unexpected{ expected: "string", found: None, at: start }),
+        };
+        let mut value = String::new();
+        loop {
+            let at = self.position.clone();
+            match self.peek_next() {
+                Some(ch) if ch == quote => { let _ = self.next(); break; },
+                Some('\\') => {
+                    let _ = self.next();
+                    let decoded = match self.next() {
+                        Some(ch @ ('\\' | '"' | '\'')) => ch,
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('0') => '\0',
+                        Some(kind @ ('u' | 'U')) => {
+                            let digits = if kind == 'u' { 4 } else { 8 };
+                            let code = self.scan_fixed_digits(digits, 16)
+                                .map_err(|_| ScanError::InvalidEscape{ at: at.clone() })?;
+                            char::from_u32(code.value).ok_or(ScanError::InvalidEscape{ at })?
+                        },
+                        _ => return Err(ScanError::InvalidEscape{ at }),
+                    };
+                    value.push(decoded);
+                },
+                Some(ch) if !is_line_terminator(ch) => { let _ = self.next(); value.push(ch); },
+                _ => return Err(ScanError::Unterminated{ start, at }),
+            }
+        }
+        Ok(self.spanned(start, value))
+    }
+
+    /// Scans text enclosed by typographic quotes (`“…”` or `‘…’`) and returns the text between
+    /// them. The quoted text may span lines; nested quotes are not recognized.
+    pub fn scan_smart_quoted(&mut self) -> Result<Spanned<String>, ScanError> {
+        let start = self.position.clone();
+        let close = match self.peek_next() {
+            Some('“') => '”',
+            Some('‘') => '’',
+            found => return Err(ScanError::Unexpected{ expected: "opening typographic quote", found, at: start }),
+        };
+        let _ = self.next();
+        let content_start = self.offset();
+        loop {
+            match self.peek_next() {
+                Some(ch) if ch == close => break,
+                Some(_) => { let _ = self.next(); },
+                None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+            }
+        }
+        let content = self.source[content_start..self.offset()].to_string();
+        let _ = self.next();
+        Ok(self.spanned(start, content))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_smart_quoted() {
+        let mut text = Text::with_str("“hi” ‘ok’");
+
+        let quoted = text.scan_smart_quoted().unwrap();
+        assert_eq!(quoted.value, "hi");
+        assert_eq!(quoted.span, Span::with(Position::with(1, 1), Position::with(1, 5)));
+        let _ = text.next();
+        assert_eq!(text.scan_smart_quoted().unwrap().value, "ok");
+    }
+
+    #[test]
+    fn scan_smart_quoted_errors() {
+        let mut text = Text::with_str("“hi");
+        assert_eq!(text.scan_smart_quoted(),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 1), at: Position::with(1, 4) }));
+
+        let mut text = Text::with_str("\"hi\"");
+        assert_eq!(text.scan_smart_quoted(),
+                   Err(ScanError::Unexpected{ expected: "opening typographic quote", found: Some('"'), at: Position::with(1, 1) }));
+    }
+}
@@ -0,0 +1,196 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::ident::ident_len;
+use crate::{ScanError, Spanned, Text};
+
+/// Part of an interpolated string as returned by `Text::scan_interpolated()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// literal text (escape sequences are not resolved)
+    Literal(&'a str),
+    /// content of an interpolation without the surrounding delimiters
+    Expr(&'a str),
+}
+
+impl<'a> Text<'a> {
+    /// Splits the rest of the text into literal segments and interpolations that start with
+    /// `open` (e.g. `${`) and end with `close` (e.g. `}`).
+    /// Within an interpolation the last char of `open` starts a nested pair which must be closed
+    /// first, so that `${ {a} }` is a single interpolation. Within literal text a backslash
+    /// escapes the following char, i.e. `\${` does not start an interpolation.
+    /// The span of an interpolation covers its delimiters.
+    ///
+    /// # Panics
+    /// Panics if `open` is empty.
+    pub fn scan_interpolated(&mut self, open: &str, close: char) -> Result<Vec<Spanned<Segment<'a>>>, ScanError> {
+        assert!(!open.is_empty(), "interpolation opener must not be empty");
+        let nested = open.chars().last();
+        let mut segments = Vec::new();
+        let mut literal = (self.offset(), self.position.clone());
+
+        while let Some(ch) = self.peek_next() {
+            if ch == '\\' {
+                let _ = self.next();
+                let _ = self.next();
+                continue;
+            }
+            if !self.remaining().starts_with(open) {
+                let _ = self.next();
+                continue;
+            }
+
+            if literal.0 < self.offset() {
+                let value = Segment::Literal(&self.source[literal.0..self.offset()]);
+                segments.push(self.spanned(literal.1, value));
+            }
+            let start = self.position.clone();
+            self.consume_bytes(open.len());
+            let expr_start = self.offset();
+            let mut depth = 0usize;
+            let expr_end = loop {
+                match self.peek_next() {
+                    None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+                    Some(ch) if ch == close && depth == 0 => break self.offset(),
+                    Some(ch) if ch == close => depth -= 1,
+                    Some(ch) if Some(ch) == nested => depth += 1,
+                    Some(_) => {},
+                }
+                let _ = self.next();
+            };
+            let _ = self.next();
+            segments.push(self.spanned(start, Segment::Expr(&self.source[expr_start..expr_end])));
+            literal = (self.offset(), self.position.clone());
+        }
+
+        if literal.0 < self.offset() {
+            let value = Segment::Literal(&self.source[literal.0..self.offset()]);
+            segments.push(self.spanned(literal.1, value));
+        }
+        Ok(segments)
+    }
+
+    /// Scans the rest of a shell parameter expansion `${NAME}` or `${NAME:-default}` after the
+    /// opening `${` has been consumed, including the closing `}`. Returns the name and the
+    /// optional default value, which may contain nested pairs of braces.
+    pub fn scan_shell_param(&mut self) -> Result<Spanned<(String, Option<String>)>, ScanError> {
+        let start = self.position.clone();
+        let len = ident_len(self.remaining(), &[], &[]);
+        if len == 0 {
+            return Err(match self.peek_next() {
+                None => ScanError::Unterminated{ start, at: self.position.clone() },
+                found => ScanError::Unexpected{ expected: "parameter name", found, at: self.position.clone() },
+            });
+        }
+        let name = self.consume_bytes(len).to_string();
+
+        let mut default = None;
+        if self.remaining().starts_with(":-") {
+            self.consume_bytes(2);
+            let default_start = self.offset();
+            let mut depth = 0usize;
+            loop {
+                match self.peek_next() {
+                    Some('}') if depth == 0 => break,
+                    Some('}') => depth -= 1,
+                    Some('{') => depth += 1,
+                    Some(_) => {},
+                    None => return Err(ScanError::Unterminated{ start, at: self.position.clone() }),
+                }
+                let _ = self.next();
+            }
+            default = Some(self.source[default_start..self.offset()].to_string());
+        }
+
+        if self.peek_next().is_none() {
+            return Err(ScanError::Unterminated{ start, at: self.position.clone() });
+        }
+        self.expect_char('}')?;
+        Ok(self.spanned(start, (name, default)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_interpolated() {
+        let mut text = Text::with_str("a${x}b");
+
+        let segments = text.scan_interpolated("${", '}').unwrap();
+        assert_eq!(segments, vec![
+            Spanned::with(Segment::Literal("a"), Span::with(Position::with(1, 1), Position::with(1, 2))),
+            Spanned::with(Segment::Expr("x"), Span::with(Position::with(1, 2), Position::with(1, 6))),
+            Spanned::with(Segment::Literal("b"), Span::with(Position::with(1, 6), Position::with(1, 7))),
+        ]);
+    }
+
+    #[test]
+    fn scan_interpolated_nested_and_escaped() {
+        let mut text = Text::with_str("${ {a} }\\${b}");
+
+        let segments = text.scan_interpolated("${", '}').unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].value, Segment::Expr(" {a} "));
+        assert_eq!(segments[1].value, Segment::Literal("\\${b}"));
+    }
+
+    #[test]
+    fn scan_interpolated_unterminated() {
+        let mut text = Text::with_str("a\n${x");
+
+        assert_eq!(text.scan_interpolated("${", '}'),
+                   Err(ScanError::Unterminated{ start: Position::with(2, 1), at: Position::with(2, 4) }));
+    }
+
+    #[test]
+    fn scan_shell_param() {
+        let mut text = Text::with_str("${FOO} ${FOO:-bar}");
+
+        text.consume_bytes(2);
+        let param = text.scan_shell_param().unwrap();
+        assert_eq!(param.value, ("FOO".to_string(), None));
+        assert_eq!(param.span, Span::with(Position::with(1, 3), Position::with(1, 7)));
+        text.consume_bytes(3);
+        assert_eq!(text.scan_shell_param().unwrap().value, ("FOO".to_string(), Some("bar".to_string())));
+        assert_eq!(text.next(), None);
+    }
+
+    #[test]
+    fn scan_shell_param_errors() {
+        let mut text = Text::with_str("${");
+        text.consume_bytes(2);
+        assert_eq!(text.scan_shell_param(),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 3), at: Position::with(1, 3) }));
+
+        let mut text = Text::with_str("${}");
+        text.consume_bytes(2);
+        assert_eq!(text.scan_shell_param(),
+                   Err(ScanError::Unexpected{ expected: "parameter name", found: Some('}'), at: Position::with(1, 3) }));
+
+        let mut text = Text::with_str("${A:-b");
+        text.consume_bytes(2);
+        assert_eq!(text.scan_shell_param(),
+                   Err(ScanError::Unterminated{ start: Position::with(1, 3), at: Position::with(1, 7) }));
+    }
+
+    #[test]
+    #[should_panic(expected = "interpolation opener must not be empty")]
+    fn scan_interpolated_empty_opener() {
+        let _ = Text::with_str("a}").scan_interpolated("", '}');
+    }
+}
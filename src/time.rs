@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Spanned, Text};
+
+/// Returns the length in bytes of the ISO-8601 timestamp `YYYY-MM-DDThh:mm:ss[.f][Z|±hh:mm]` at
+/// the start of `s` or `None` if `s` does not start with one.
+fn iso8601_len(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    let digits = |at: usize, n: usize| b.len() >= at + n && b[at..at + n].iter().all(u8::is_ascii_digit);
+    let byte = |at: usize, expected: u8| b.get(at) == Some(&expected);
+
+    let shape = digits(0, 4) && byte(4, b'-') && digits(5, 2) && byte(7, b'-') && digits(8, 2)
+        && byte(10, b'T') && digits(11, 2) && byte(13, b':') && digits(14, 2) && byte(16, b':')
+        && digits(17, 2);
+    if !shape {
+        return None;
+    }
+    let mut len = 19;
+    if byte(len, b'.') {
+        let fraction = b[len + 1..].iter().take_while(|b| b.is_ascii_digit()).count();
+        if fraction > 0 {
+            len += 1 + fraction;
+        }
+    }
+    if byte(len, b'Z') {
+        len += 1;
+    }
+    else if (byte(len, b'+') || byte(len, b'-')) && digits(len + 1, 2) && byte(len + 3, b':') && digits(len + 4, 2) {
+        len += 6;
+    }
+    Some(len)
+}
+
+/// Units accepted by `Text::scan_duration()`, longer units sharing a prefix first.
+const DURATION_UNITS: &[&str] = &["ns", "us", "µs", "ms", "s", "min", "m", "h", "d"];
+
+/// Returns the magnitude of the duration component at the start of `s` together with the
+/// lengths in bytes of its digits and of its unit.
+fn duration_component(s: &str) -> Option<(u64, usize, usize)> {
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    let magnitude = s[..digits].parse().ok()?;
+    let rest = &s[digits..];
+    let unit = DURATION_UNITS.iter().find(|unit| {
+        rest.starts_with(*unit) && !rest[unit.len()..].starts_with(char::is_alphabetic)
+    })?;
+    Some((magnitude, digits, unit.len()))
+}
+
+impl<'a> Text<'a> {
+    /// Scans a compact duration like `1h30m` or `500ms` consisting of pairs of an integer and a
+    /// unit (`ns`, `us`, `µs`, `ms`, `s`, `min`, `m`, `h`, `d`) and returns the pairs.
+    /// Scanning stops before the first pair with an unknown unit.
+    /// Returns `None` without consuming anything if no valid pair starts at the reading position.
+    pub fn scan_duration(&mut self) -> Option<Spanned<Vec<(u64, &'a str)>>> {
+        let start = self.position.clone();
+        let mut components = Vec::new();
+        while let Some((magnitude, digits, unit_len)) = duration_component(self.remaining()) {
+            self.consume_bytes(digits);
+            components.push((magnitude, self.consume_bytes(unit_len)));
+        }
+        if components.is_empty() {
+            return None;
+        }
+        Some(self.spanned(start, components))
+    }
+
+    /// Scans a timestamp of the form `YYYY-MM-DDThh:mm:ss` with optional fractional seconds and
+    /// an optional timezone (`Z` or `±hh:mm`). Only the lexical shape is checked, not whether the
+    /// fields are in range.
+    /// Returns `None` without consuming anything if no timestamp starts at the reading position.
+    pub fn scan_iso8601(&mut self) -> Option<Spanned<&'a str>> {
+        let len = iso8601_len(self.remaining())?;
+        let start = self.position.clone();
+        let timestamp = self.consume_bytes(len);
+        Some(self.spanned(start, timestamp))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_iso8601() {
+        let mut text = Text::with_str("2024-02-29T13:05:59.123+01:00 started");
+
+        let timestamp = text.scan_iso8601().unwrap();
+        assert_eq!(timestamp.value, "2024-02-29T13:05:59.123+01:00");
+        assert_eq!(timestamp.span, Span::with(Position::with(1, 1), Position::with(1, 30)));
+        assert_eq!(text.next(), Some(' '));
+
+        let mut text = Text::with_str("2024-02-29T13:05:59Z");
+        assert_eq!(text.scan_iso8601().unwrap().value, "2024-02-29T13:05:59Z");
+    }
+
+    #[test]
+    fn scan_iso8601_date_only() {
+        let mut text = Text::with_str("2024-02-29 started");
+
+        assert_eq!(text.scan_iso8601(), None);
+        assert_eq!(text.position(), &Position::with(1, 1));
+    }
+
+    #[test]
+    fn scan_duration() {
+        let mut text = Text::with_str("1h30m 500ms 2s10x");
+
+        let duration = text.scan_duration().unwrap();
+        assert_eq!(duration.value, vec![(1, "h"), (30, "m")]);
+        assert_eq!(duration.span, Span::with(Position::with(1, 1), Position::with(1, 6)));
+        let _ = text.next();
+        assert_eq!(text.scan_duration().unwrap().value, vec![(500, "ms")]);
+        let _ = text.next();
+        assert_eq!(text.scan_duration().unwrap().value, vec![(2, "s")]);
+        assert_eq!(text.next(), Some('1'));
+    }
+
+    #[test]
+    fn scan_duration_unknown_unit() {
+        let mut text = Text::with_str("10x");
+
+        assert_eq!(text.scan_duration(), None);
+        assert_eq!(text.position(), &Position::with(1, 1));
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{LineEnding, Spanned, Text};
+
+/// Element of a token stream that keeps all whitespace, see `Text::lex_with_trivia()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Trivia<'a, T> {
+    /// run of whitespace within a line
+    Whitespace(Spanned<&'a str>),
+    /// line terminator
+    Newline(Spanned<LineEnding>),
+    /// token returned by the content scanner
+    Token(Spanned<T>),
+    /// text the content scanner did not accept
+    Unrecognized(Spanned<&'a str>),
+}
+
+impl<'a> Text<'a> {
+    /// Splits the whole text into whitespace runs, line terminators and the tokens returned by
+    /// `content`, so that the source text can be reconstructed exactly from the result.
+    /// If `content` returns `None` or does not consume anything, the skipped text (at least one
+    /// char) is returned as `Trivia::Unrecognized`.
+    pub fn lex_with_trivia<T>(mut self, mut content: impl FnMut(&mut Text<'a>) -> Option<Spanned<T>>) -> Vec<Trivia<'a, T>> {
+        let mut trivia = Vec::new();
+        while self.peek_next().is_some() {
+            let start = self.position.clone();
+            let whitespace = self.consume_while_on_line(char::is_whitespace);
+            if !whitespace.is_empty() {
+                trivia.push(Trivia::Whitespace(self.spanned(start, whitespace)));
+                continue;
+            }
+            if let Some(ending) = self.scan_line_ending() {
+                trivia.push(Trivia::Newline(ending));
+                continue;
+            }
+
+            let offset = self.offset();
+            let token = content(&mut self);
+            let recognized = token.is_some();
+            if let Some(token) = token {
+                trivia.push(Trivia::Token(token));
+            }
+            if !recognized || self.offset() == offset {
+                let start = self.position.clone();
+                if self.offset() == offset {
+                    let _ = self.next();
+                }
+                let skipped = &self.source[offset..self.offset()];
+                trivia.push(Trivia::Unrecognized(self.spanned(start, skipped)));
+            }
+        }
+        trivia
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn lex_with_trivia() {
+        let source = "a = b;\r\n  c\n";
+        let trivia = Text::with_str(source).lex_with_trivia(|text| {
+            text.scan_ident_with(&[], &[]).or_else(|| text.scan_operator(&["="]))
+        });
+
+        assert_eq!(trivia[0], Trivia::Token(Spanned::with("a", Span::with(Position::with(1, 1), Position::with(1, 2)))));
+        assert_eq!(trivia[1], Trivia::Whitespace(Spanned::with(" ", Span::with(Position::with(1, 2), Position::with(1, 3)))));
+        assert_eq!(trivia[6], Trivia::Newline(Spanned::with(LineEnding::CrLf, Span::with(Position::with(1, 7), Position::with(2, 1)))));
+
+        let restored: String = trivia.iter().map(|trivia| match trivia {
+            Trivia::Whitespace(ws) => ws.value,
+            Trivia::Newline(ending) => ending.value.as_str(),
+            Trivia::Token(token) => token.value,
+            Trivia::Unrecognized(skipped) => skipped.value,
+        }).collect();
+        assert_eq!(restored, source);
+        assert!(matches!(trivia[5], Trivia::Unrecognized(Spanned{ value: ";", .. })));
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Spanned, Text};
+
+/// Returns `true` for the chars allowed in a URI besides `%`-encoded octets.
+fn is_uri_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:/?#[]@".contains(&b)
+}
+
+/// Returns the length of the URI at the start of `s` before trailing punctuation is removed.
+fn uri_run_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut len = 0;
+    while len < bytes.len() {
+        match bytes[len] {
+            b'%' if bytes.len() > len + 2 && bytes[len + 1].is_ascii_hexdigit() && bytes[len + 2].is_ascii_hexdigit() =>
+                len += 3,
+            b if is_uri_byte(b) => len += 1,
+            _ => break,
+        }
+    }
+    len
+}
+
+impl<'a> Text<'a> {
+    /// Scans a URI consisting of a scheme followed by `://` and a run of URI chars (unreserved
+    /// chars, sub-delimiters, `%`-encoded octets and `:/?#[]@`). Trailing punctuation like `.`,
+    /// `,` or `!` and a trailing `)` without matching `(` are not part of the URI, so that URIs
+    /// within prose are recognized.
+    /// Returns `None` without consuming anything if there is no scheme or nothing follows `://`.
+    pub fn scan_uri(&mut self) -> Option<Spanned<&'a str>> {
+        let rest = self.remaining();
+        let scheme = rest.bytes()
+            .take_while(|&b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-' || b == b'.')
+            .count();
+        if scheme == 0 || !rest.starts_with(|ch: char| ch.is_ascii_alphabetic()) || !rest[scheme..].starts_with("://") {
+            return None;
+        }
+        let prefix = scheme + 3;
+        let mut uri = &rest[prefix..prefix + uri_run_len(&rest[prefix..])];
+        loop {
+            let unbalanced = uri.ends_with(')') && uri.matches(')').count() > uri.matches('(').count();
+            if !unbalanced && !uri.ends_with(['.', ',', ';', ':', '!', '?', '\'']) {
+                break;
+            }
+            uri = &uri[..uri.len() - 1];
+        }
+        if uri.is_empty() {
+            return None;
+        }
+        let start = self.position.clone();
+        let uri = self.consume_bytes(prefix + uri.len());
+        Some(self.spanned(start, uri))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn scan_uri() {
+        let mut text = Text::with_str("https://example.com/path?q=1%20x rest");
+
+        let uri = text.scan_uri().unwrap();
+        assert_eq!(uri.value, "https://example.com/path?q=1%20x");
+        assert_eq!(uri.span, Span::with(Position::with(1, 1), Position::with(1, 33)));
+        assert_eq!(text.next(), Some(' '));
+        assert_eq!(text.scan_uri(), None);
+    }
+
+    #[test]
+    fn scan_uri_trailing_punctuation() {
+        let mut text = Text::with_str("http://a.org/x.");
+        assert_eq!(text.scan_uri().unwrap().value, "http://a.org/x");
+        assert_eq!(text.next(), Some('.'));
+
+        let mut text = Text::with_str("ftp://w.org/A_(b)).");
+        assert_eq!(text.scan_uri().unwrap().value, "ftp://w.org/A_(b)");
+        assert_eq!(text.next(), Some(')'));
+    }
+}
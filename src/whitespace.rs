@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{is_line_terminator, line_terminator_len, LineEnding, Position, ScanError, Span, Spanned, Text};
+
+impl<'a> Text<'a> {
+    /// Consumes all whitespace characters (including line terminators) and returns the number
+    /// of line breaks that were crossed. A `\r\n` sequence counts as one line break.
+    pub fn skip_ws_counting_newlines(&mut self) -> usize {
+        let start_line = self.position.line;
+        while let Some(ch) = self.peek_next() {
+            if !ch.is_whitespace() {
+                break;
+            }
+            let _ = self.next();
+        }
+        (self.position.line - start_line) as usize
+    }
+
+    /// Consumes all whitespace characters (including line terminators) and returns the
+    /// positions of the consumed line terminators. For `\r\n` only the position of `\r` is
+    /// returned.
+    pub fn skip_ws_collecting_newlines(&mut self) -> Vec<Position> {
+        let mut newlines = Vec::new();
+        let mut last = None;
+        while let Some(ch) = self.peek_next() {
+            if !ch.is_whitespace() {
+                break;
+            }
+            if is_line_terminator(ch) && !(ch == '\n' && last == Some('\r')) {
+                newlines.push(self.position.clone());
+            }
+            last = self.next();
+        }
+        newlines
+    }
+
+    /// Consumes whitespace between the reading position and the end of the line (or text) and
+    /// returns its span, which is useful for a "no trailing whitespace" lint. The line terminator
+    /// is not consumed.
+    /// Returns `None` without consuming anything if there is no whitespace or if it is followed
+    /// by further content on the same line.
+    pub fn lint_line_trailing(&mut self) -> Option<Span> {
+        let rest = self.remaining();
+        let len = rest.find(|ch: char| is_line_terminator(ch) || !ch.is_whitespace()).unwrap_or(rest.len());
+        if len == 0 || (len < rest.len() && line_terminator_len(&rest[len..]) == 0) {
+            return None;
+        }
+        let start = self.position.clone();
+        self.consume_bytes(len);
+        Some(Span::with(start, self.position.clone()))
+    }
+
+    /// Scans the end of a line, i.e. spaces and tabs followed by a line terminator, and returns
+    /// the spaces and tabs together with the kind of the terminator. The span covers both.
+    /// Returns `None` without consuming anything if the next chars are no end of a line.
+    pub fn scan_eol(&mut self) -> Option<Spanned<(&'a str, LineEnding)>> {
+        let rest = self.remaining();
+        let len = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        let ending = LineEnding::at_start(&rest[len..])?;
+        let start = self.position.clone();
+        let trailing = self.consume_bytes(len);
+        self.consume_bytes(ending.as_str().len());
+        Some(self.spanned(start, (trailing, ending)))
+    }
+
+    /// Consumes exactly one space (U+0020). Returns `ScanError::ExpectedSpace` without consuming
+    /// anything if the next char is no space or if the space is followed by another space or a tab.
+    /// A space followed by a line terminator is accepted.
+    pub fn expect_single_space(&mut self) -> Result<(), ScanError> {
+        let mut chars = self.remaining().chars();
+        if chars.next() != Some(' ') {
+            return Err(ScanError::ExpectedSpace{ at: self.position.clone() });
+        }
+        if let Some(' ' | '\t') = chars.next() {
+            let at = Position::with(self.position.line, self.position.column + 1);
+            return Err(ScanError::ExpectedSpace{ at });
+        }
+        let _ = self.next();
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn skip_ws_counting_newlines() {
+        let mut text = Text::with_str("  \n\n x");
+
+        assert_eq!(text.skip_ws_counting_newlines(), 2);
+        assert_eq!(text.position(), &Position::with(3, 2));
+        assert_eq!(text.peek_next(), Some('x'));
+        assert_eq!(text.skip_ws_counting_newlines(), 0);
+    }
+
+    #[test]
+    fn skip_ws_counting_crlf() {
+        let mut text = Text::with_str("\r\n\n\r\nx");
+
+        assert_eq!(text.skip_ws_counting_newlines(), 3);
+        assert_eq!(text.next(), Some('x'));
+    }
+
+    #[test]
+    fn skip_ws_collecting_newlines() {
+        let mut text = Text::with_str("a \n\n b");
+
+        let _ = text.next();
+        assert_eq!(text.skip_ws_collecting_newlines(), vec![Position::with(1, 3), Position::with(2, 1)]);
+        assert_eq!(text.position(), &Position::with(3, 2));
+        assert_eq!(text.next(), Some('b'));
+    }
+
+    #[test]
+    fn lint_line_trailing() {
+        let mut text = Text::with_str("a  \nb c\t");
+
+        let _ = text.next();
+        assert_eq!(text.lint_line_trailing(), Some(Span::with(Position::with(1, 2), Position::with(1, 4))));
+        assert_eq!(text.next(), Some('\n'));
+        assert_eq!(text.lint_line_trailing(), None);
+        let _ = text.next();
+        assert_eq!(text.lint_line_trailing(), None);
+        assert_eq!(text.next(), Some(' '));
+        let _ = text.next();
+        assert_eq!(text.lint_line_trailing(), Some(Span::with(Position::with(2, 4), Position::with(2, 5))));
+    }
+
+    #[test]
+    fn scan_eol() {
+        let mut text = Text::with_str("  \r\n\nx \t");
+
+        let eol = text.scan_eol().unwrap();
+        assert_eq!(eol.value, ("  ", LineEnding::CrLf));
+        assert_eq!(eol.span, Span::with(Position::with(1, 1), Position::with(2, 1)));
+        assert_eq!(text.scan_eol().unwrap().value, ("", LineEnding::Lf));
+        assert_eq!(text.scan_eol(), None);
+        let _ = text.next();
+        assert_eq!(text.scan_eol(), None);
+        assert_eq!(text.next(), Some(' '));
+    }
+
+    #[test]
+    fn expect_single_space() {
+        let mut text = Text::with_str("a b\tc  d");
+
+        let _ = text.next();
+        assert_eq!(text.expect_single_space(), Ok(()));
+        assert_eq!(text.next(), Some('b'));
+        assert_eq!(text.expect_single_space(), Err(ScanError::ExpectedSpace{ at: Position::with(1, 4) }));
+        let _ = text.next();
+        let _ = text.next();
+        assert_eq!(text.expect_single_space(), Err(ScanError::ExpectedSpace{ at: Position::with(1, 7) }));
+        assert_eq!(text.position(), &Position::with(1, 6));
+
+        let mut text = Text::with_str("");
+        assert_eq!(text.expect_single_space(), Err(ScanError::ExpectedSpace{ at: Position::with(1, 1) }));
+
+        let mut text = Text::with_str(" \n");
+        assert_eq!(text.expect_single_space(), Ok(()));
+        assert_eq!(text.next(), Some('\n'));
+    }
+}
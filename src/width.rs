@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{is_line_terminator, Counter, Text};
+
+/// Returns the number of columns `ch` occupies on a terminal or in an editor: 2 for wide
+/// (e.g. CJK) characters, 0 for combining and other zero-width characters and 1 otherwise.
+/// Tabs are not expanded here, see `tab_advance()`.
+pub fn char_width(ch: char) -> Counter {
+    match ch as u32 {
+        0x0300..=0x036f | 0x1ab0..=0x1aff | 0x1dc0..=0x1dff | 0x200b..=0x200f
+        | 0x20d0..=0x20ff | 0xfe00..=0xfe0f | 0xfe20..=0xfe2f => 0,
+        0x1100..=0x115f | 0x2e80..=0x303e | 0x3041..=0x33ff | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff | 0xa000..=0xa4cf | 0xac00..=0xd7a3 | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f | 0xff00..=0xff60 | 0xffe0..=0xffe6 | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff | 0x20000..=0x2fffd | 0x30000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+/// Returns the number of columns a tab occupies when it starts at display column `column`
+/// (starting with 1). A `tab_width` of 0 is treated as 1, a `column` of 0 like column 1.
+pub fn tab_advance(column: Counter, tab_width: Counter) -> Counter {
+    let tab_width = tab_width.max(1);
+    tab_width - column.saturating_sub(1) % tab_width
+}
+
+/// Returns the byte index of the character within `line` that covers the display column `col`
+/// (starting with 1) when tabs are expanded to `tab_width`. The column directly after the last
+/// character maps to `line.len()`; columns further right and column 0 return `None`.
+pub fn display_column_to_byte(line: &str, col: Counter, tab_width: Counter) -> Option<usize> {
+    if col == 0 {
+        return None;
+    }
+    let mut column = 1;
+    for (index, ch) in line.char_indices() {
+        let width = if ch == '\t' { tab_advance(column, tab_width) } else { char_width(ch) };
+        if col < column.saturating_add(width) {
+            return Some(index);
+        }
+        column = column.saturating_add(width);
+    }
+    if col == column { Some(line.len()) } else { None }
+}
+
+impl<'a> Text<'a> {
+    /// Consumes characters as long as their accumulated display width (see `char_width()`) does
+    /// not exceed `max_width` and returns them. Tab stops are measured from the reading position
+    /// using `tab_width()`. Scanning always stops at a line terminator.
+    pub fn consume_to_width(&mut self, max_width: Counter) -> &'a str {
+        let start = self.offset();
+        let mut width: Counter = 0;
+        while let Some(ch) = self.peek_next() {
+            if is_line_terminator(ch) {
+                break;
+            }
+            let ch_width = if ch == '\t' { tab_advance(width.saturating_add(1), self.tab_width) } else { char_width(ch) };
+            match width.checked_add(ch_width) {
+                Some(new_width) if new_width <= max_width => width = new_width,
+                _ => break,
+            }
+            let _ = self.next();
+        }
+        &self.source[start..self.offset()]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn tab_advance_columns() {
+        assert_eq!(tab_advance(1, 4), 4);
+        assert_eq!(tab_advance(3, 4), 2);
+        assert_eq!(tab_advance(5, 4), 4);
+        assert_eq!(tab_advance(0, 4), 4);
+        assert_eq!(tab_advance(2, 0), 1);
+    }
+
+    #[test]
+    fn huge_tab_width() {
+        assert_eq!(display_column_to_byte("\tx", Counter::MAX - 1, Counter::MAX), Some(0));
+
+        let mut text = Text::with_str("\t\tx: 1");
+        text.set_tab_width(Counter::MAX);
+        assert_eq!(text.scan_indented_block(Counter::MAX).value, "\t\tx: 1");
+    }
+
+    #[test]
+    fn display_column_to_byte_tabs() {
+        assert_eq!(display_column_to_byte("\tab", 1, 4), Some(0));
+        assert_eq!(display_column_to_byte("\tab", 4, 4), Some(0));
+        assert_eq!(display_column_to_byte("\tab", 5, 4), Some(1));
+        assert_eq!(display_column_to_byte("a\tb", 2, 4), Some(1));
+        assert_eq!(display_column_to_byte("a\tb", 5, 4), Some(2));
+        assert_eq!(display_column_to_byte("a\tb", 6, 4), Some(3));
+        assert_eq!(display_column_to_byte("a\tb", 7, 4), None);
+        assert_eq!(display_column_to_byte("a\tb", 0, 4), None);
+    }
+
+    #[test]
+    fn display_column_to_byte_wide() {
+        let line = "日本x";
+        assert_eq!(display_column_to_byte(line, 2, 4), Some(0));
+        assert_eq!(display_column_to_byte(line, 3, 4), Some(3));
+        assert_eq!(display_column_to_byte(line, 5, 4), Some(6));
+        assert_eq!(display_column_to_byte(line, 6, 4), Some(7));
+        assert_eq!(display_column_to_byte("e\u{301}x", 2, 4), Some(3));
+    }
+
+    #[test]
+    fn consume_to_width() {
+        let mut text = Text::with_str("日本語のテキスト");
+        assert_eq!(text.consume_to_width(5), "日本");
+        assert_eq!(text.position(), &Position::with(1, 3));
+        assert_eq!(text.consume_to_width(6), "語のテ");
+
+        let mut text = Text::with_str("a\tbc\nd");
+        assert_eq!(text.consume_to_width(5), "a\tb");
+        assert_eq!(text.consume_to_width(5), "c");
+        assert_eq!(text.next(), Some('\n'));
+
+        let mut text = Text::with_str("\t\tx");
+        text.set_tab_width(Counter::MAX);
+        assert_eq!(text.consume_to_width(Counter::MAX), "\t");
+    }
+}
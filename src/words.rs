@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: GPL-2.0
+//
+// This file is part of chscn.
+// chscn is free software: you can redistribute it and/or modify it under the terms of the
+// GNU General Public License as published by the Free Software Foundation, either version 2
+// of the License, or (at your option) any later version.
+//
+// chscn is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Foobar.
+// If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{Spanned, Text};
+
+/// Iterator over the words of a text, see `Text::words()`.
+#[derive(Clone, Debug)]
+pub struct Words<'a> {
+    text: Text<'a>,
+}
+
+/// Returns `true` for the chars words consist of (alphanumeric chars and `_`); this simplified
+/// rule stands in for the Unicode word boundary rules of UAX #29.
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+/// Returns the byte length of the word at the start of `s`. Apostrophes join letters (`don't`),
+/// full stops and commas join digits (`3.14`, `1,000`).
+fn word_len(s: &str) -> usize {
+    let mut len = 0;
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if !is_word_char(ch) {
+            let prev = s[..len].chars().next_back();
+            let joins = |a: char, b: char| match ch {
+                '\'' | '\u{2019}' => a.is_alphabetic() && b.is_alphabetic(),
+                '.' | ',' => a.is_numeric() && b.is_numeric(),
+                _ => false,
+            };
+            match (prev, chars.peek()) {
+                (Some(a), Some(&b)) if joins(a, b) => {},
+                _ => break,
+            }
+        }
+        len += ch.len_utf8();
+    }
+    len
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = Spanned<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.text.peek_next().is_some_and(|ch| !is_word_char(ch)) {
+            let _ = self.text.next();
+        }
+        let len = word_len(self.text.remaining());
+        if len == 0 {
+            return None;
+        }
+        let start = self.text.position.clone();
+        let word = self.text.consume_bytes(len);
+        Some(self.text.spanned(start, word))
+    }
+}
+
+impl<'a> Text<'a> {
+    /// Returns an iterator over the words of the remaining text. A word is a run of alphanumeric
+    /// chars and underscores; whitespace and punctuation separate words, except for apostrophes
+    /// between letters and full stops or commas between digits.
+    pub fn words(self) -> Words<'a> {
+        Words{ text: self }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn words() {
+        let words: Vec<_> = Text::with_str("Hello, world!").words().collect();
+
+        assert_eq!(words, vec![
+            Spanned::with("Hello", Span::with(Position::with(1, 1), Position::with(1, 6))),
+            Spanned::with("world", Span::with(Position::with(1, 8), Position::with(1, 13))),
+        ]);
+    }
+
+    #[test]
+    fn words_joined() {
+        let words: Vec<_> = Text::with_str("it's 3.14, 'quoted'\nend.").words().map(|w| w.value).collect();
+
+        assert_eq!(words, vec!["it's", "3.14", "quoted", "end"]);
+    }
+}